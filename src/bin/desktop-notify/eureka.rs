@@ -1,19 +1,17 @@
-use super::APP_ID;
+use super::notification;
 use eureka_notify::prelude::*;
 use chrono::{Duration, Utc};
 use tokio::time::sleep;
 use tracing::*;
-use notify_rust::Notification;
-use chrono_humanize::HumanTime;
+use chrono_humanize::{Accuracy, HumanTime, Tense};
 
 enum TimeSleep {
     OneCycle,
-    FiveMinutes,
-    OneMinute,
+    Before(Duration),
     Now,
 }
 
-pub async fn run_loop() {
+pub async fn run_loop(leads: Vec<Duration>) {
     info!("Starting eureka loop");
 
     let mut now = DateTimeEorzea::now().truncated(Duration::hours(8));
@@ -21,32 +19,32 @@ pub async fn run_loop() {
     loop {
         let future = now + Duration::hours(8);
 
-        let crab = crab_status(now);
-        let crab = if crab == future { Some(crab) } else { None };
-
-        let cassie = cassie_status(now);
-        let cassie = if cassie == future { Some(cassie) } else { None };
-
-        let skoll = skoll_status(now);
-        let skoll = if skoll == future { Some(skoll) } else { None };
-
-        let do_notify = crab.is_some() || cassie.is_some() || skoll.is_some();
-
-        if do_notify {
-            notify_os(TimeSleep::OneCycle, crab, cassie, skoll);
+        let starting = Tracker::nms_starting(future);
+        notify_os(TimeSleep::OneCycle, &starting, future);
+
+        // Lead times longer than a cycle alert for windows a few cycles ahead
+        let mut alerts = vec![];
+        for lead in &leads {
+            let mut start = future;
+            while start.to_utc() - *lead < future.to_utc() {
+                if start.to_utc() - *lead >= now.to_utc() {
+                    alerts.push((start.to_utc() - *lead, start, *lead));
+                }
+                start += Duration::hours(8);
+            }
+        }
+        alerts.sort_by_key(|(time, _, _)| *time);
 
-            // Wait until 5 minutes before the next cycle to send another notification
-            if let Ok(duration) = ((future.to_utc() - Duration::minutes(5)) - Utc::now()).to_std() {
-                info!("sleep for {:?} to 5-minute notification", duration);
-                sleep(duration).await;
-                notify_os(TimeSleep::FiveMinutes, crab, cassie, skoll);
+        for (time, start, lead) in alerts {
+            let nms = Tracker::nms_starting(start);
+            if nms.is_empty() {
+                continue;
             }
 
-            // Wait to send another notification
-            if let Ok(duration) = ((future.to_utc() - Duration::minutes(1)) - Utc::now()).to_std() {
-                info!("sleep for {:?} to 1-min notification", duration);
+            if let Ok(duration) = (time - Utc::now()).to_std() {
+                info!("sleep for {:?} to {:?} notification", duration, lead);
                 sleep(duration).await;
-                notify_os(TimeSleep::OneMinute, crab, cassie, skoll);
+                notify_os(TimeSleep::Before(lead), &nms, start);
             }
         }
 
@@ -55,60 +53,25 @@ pub async fn run_loop() {
             sleep(duration).await;
         }
 
-        if do_notify {
-            notify_os(TimeSleep::Now, crab, cassie, skoll);
-        }
+        notify_os(TimeSleep::Now, &starting, future);
 
         now = future;
     }
 }
 
-fn notify_os(timesleep: TimeSleep, crab: Option<DateTimeEorzea>, cassie: Option<DateTimeEorzea>, skoll: Option<DateTimeEorzea>) {
-    if let Some(dt) = crab {
+/// Shows an OS notification for each NM whose window opens at `start`
+fn notify_os(timesleep: TimeSleep, nms: &[Tracker], start: DateTimeEorzea) {
+    for nm in nms {
         let length = match timesleep {
-            TimeSleep::OneCycle => format!("{}", HumanTime::from(dt.to_utc())),
-            TimeSleep::FiveMinutes => "in 5 minutes".into(),
-            TimeSleep::OneMinute => "in 1 minute".into(),
+            TimeSleep::OneCycle => format!("{}", HumanTime::from(start.to_utc())),
+            TimeSleep::Before(lead) => HumanTime::from(lead).to_text_en(Accuracy::Precise, Tense::Future),
             TimeSleep::Now => "now".into(),
         };
-        Notification::new()
-            .app_id(APP_ID)
-            .summary("Crab")
-            .body(&format!("{length}"))
+        notification()
+            .summary(&nm.to_name())
+            .body(&length)
             .sound_name("Default")
             .show()
             .expect("failed to open OS notification");
     }
-
-    if let Some(dt) = cassie {
-        let length = match timesleep {
-            TimeSleep::OneCycle => format!("{}", HumanTime::from(dt.to_utc())),
-            TimeSleep::FiveMinutes => "in 5 minutes".into(),
-            TimeSleep::OneMinute => "in 1 minute".into(),
-            TimeSleep::Now => "now".into(),
-        };
-        Notification::new()
-            .app_id(APP_ID)
-            .summary("Cassie")
-            .body(&format!("{length}"))
-            .sound_name("Default")
-            .show()
-            .expect("failed to open OS notification");
-    }
-
-    if let Some(dt) = skoll {
-        let length = match timesleep {
-            TimeSleep::OneCycle => format!("{}", HumanTime::from(dt.to_utc())),
-            TimeSleep::FiveMinutes => "in 5 minutes".into(),
-            TimeSleep::OneMinute => "in 30 seconds".into(),
-            TimeSleep::Now => "now".into(),
-        };
-        Notification::new()
-            .app_id(APP_ID)
-            .summary("Skoll")
-            .body(&format!("{length}"))
-            .sound_name("Default")
-            .show()
-            .expect("failed to open OS notification");
-    }
-}
\ No newline at end of file
+}
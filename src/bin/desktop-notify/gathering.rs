@@ -0,0 +1,45 @@
+use super::notification;
+use eureka_notify::gathering::{next_node_spawns, NodeSpawn};
+use chrono::{Duration, Utc};
+use tokio::time::sleep;
+use tracing::*;
+use chrono_humanize::{Accuracy, HumanTime, Tense};
+
+pub async fn run_loop(watchlist: Vec<String>, lead: Duration) {
+    info!("Starting gathering loop");
+
+    let mut after = Utc::now() + lead;
+
+    loop {
+        let spawns = next_node_spawns(&watchlist, after);
+        let start = match spawns.first() {
+            Some(spawn) => spawn.start,
+            None => {
+                warn!("no upcoming spawns for the nodes in NODE_WATCHLIST");
+                return;
+            }
+        };
+
+        if let Ok(duration) = (start - lead - Utc::now()).to_std() {
+            info!("sleep for {:?} to node notification", duration);
+            sleep(duration).await;
+        }
+        notify_os(&spawns, lead);
+
+        after = start + Duration::seconds(1);
+    }
+}
+
+fn notify_os(spawns: &[NodeSpawn], lead: Duration) {
+    let length = HumanTime::from(lead).to_text_en(Accuracy::Precise, Tense::Future);
+
+    for spawn in spawns {
+        info!("sending node notification for {}", spawn.node.name);
+        notification()
+            .summary(&spawn.node.name)
+            .body(&format!("{} {}: {}", spawn.node.kind, length, spawn.node.items.join(", ")))
+            .sound_name("Default")
+            .show()
+            .expect("failed to open OS notification");
+    }
+}
@@ -1,19 +1,134 @@
 mod eureka;
+mod gathering;
 mod ocean;
 
+use std::env;
+use eureka_notify::data::Node;
+use eureka_notify::ocean::{Route, RouteTier, TierConfig, VoyageTimings};
+use eureka_notify::time::{parse_duration, parse_durations};
+use chrono::Duration;
+use notify_rust::Notification;
 use tracing::*;
 use tracing_subscriber;
 
+#[cfg(windows)]
 const APP_ID: &str = "com.squirrel.XIVLauncher.XIVLauncher";
 
+/// Starts an OS notification, shown under the launcher's name
+#[cfg(windows)]
+fn notification() -> Notification {
+    Notification::new().app_id(APP_ID).finalize()
+}
+
+/// Starts an OS notification
+/// Only Windows has app IDs, so elsewhere notifications come from this binary
+#[cfg(not(windows))]
+fn notification() -> Notification {
+    Notification::new()
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     tracing_subscriber::fmt().with_max_level(Level::INFO).init();
+    // Optionally load the .env file
+    if let Err(e) = dotenv::dotenv() {
+        error!("failed to load .env file: {e}");
+    }
+
+    // Comma-separated ocean fishing targets, e.g. OCEAN_TARGETS=Octopodes,Sothis
+    // Overrides the route tiers when set
+    let targets: Vec<String> = match env::var("OCEAN_TARGETS") {
+        Ok(list) => list.split(',').map(|name| name.trim().to_string()).filter(|name| !name.is_empty()).collect(),
+        Err(_) => vec![],
+    };
+
+    // Lowest route tier to notify for, e.g. OCEAN_THRESHOLD=VeryGood
+    // Per-route tiers can be changed with e.g. OCEAN_OVERRIDES=MerlthorDay:Best,RhotanoNight:Bad
+    let mut tiers = TierConfig::default();
+    if let Some(threshold) = env::var("OCEAN_THRESHOLD").ok().and_then(|name| RouteTier::from_name(&name)) {
+        tiers.threshold = threshold;
+    }
+    if let Ok(list) = env::var("OCEAN_OVERRIDES") {
+        for entry in list.split(',') {
+            match entry.split_once(':').and_then(|(route, tier)| Route::from_name(route.trim()).zip(RouteTier::from_name(tier.trim()))) {
+                Some((route, tier)) => { tiers.overrides.insert(route, tier); }
+                None => warn!("invalid ocean override `{entry}`"),
+            }
+        }
+    }
+
+    // Per-stop settings are comma-separated, one entry for each of the three stops
+    // e.g. OCEAN_STOP_ALERTS=true,true,false OCEAN_STOP_MINUTES=10,10,10 OCEAN_SPECTRAL_MINUTES=3,3,3
+    let mut stops = ocean::StopAlerts {
+        enabled: [true; 3],
+        timings: VoyageTimings::default(),
+    };
+    if let Some(enabled) = per_stop::<bool>("OCEAN_STOP_ALERTS") {
+        stops.enabled = enabled;
+    }
+    if let Some(minutes) = per_stop::<i64>("OCEAN_STOP_MINUTES") {
+        for (timing, minutes) in stops.timings.stops.iter_mut().zip(minutes) {
+            timing.minutes = minutes;
+        }
+    }
+    if let Some(minutes) = per_stop::<i64>("OCEAN_SPECTRAL_MINUTES") {
+        for (timing, minutes) in stops.timings.stops.iter_mut().zip(minutes) {
+            timing.spectral_minutes = minutes;
+        }
+    }
 
-    let eureka = tokio::spawn(eureka::run_loop());
-    let ocean = tokio::spawn(ocean::run_loop());
+    // How long before each Eureka window to notify, e.g. EUREKA_LEAD_TIMES=30m,10m,2m
+    let mut leads = vec![Duration::minutes(5), Duration::minutes(1)];
+    if let Ok(list) = env::var("EUREKA_LEAD_TIMES") {
+        match parse_durations(&list) {
+            Some(list) => leads = list,
+            None => warn!("invalid EUREKA_LEAD_TIMES `{list}`"),
+        }
+    }
+
+    // Comma-separated gathering nodes to notify for, e.g. NODE_WATCHLIST=Southern Thanalan Gold,Central Shroud Truffles
+    // Alerts NODE_LEAD_TIME before each spawn, e.g. NODE_LEAD_TIME=2m
+    let watchlist: Vec<String> = match env::var("NODE_WATCHLIST") {
+        Ok(list) => list.split(',').map(|name| name.trim().to_string()).filter(|name| !name.is_empty()).collect(),
+        Err(_) => vec![],
+    };
+    for name in watchlist.iter().filter(|name| Node::from_name(name).is_none()) {
+        warn!("unknown node `{name}` in NODE_WATCHLIST");
+    }
+    let mut node_lead = Duration::minutes(2);
+    if let Ok(text) = env::var("NODE_LEAD_TIME") {
+        match parse_duration(&text) {
+            Some(lead) => node_lead = lead,
+            None => warn!("invalid NODE_LEAD_TIME `{text}`"),
+        }
+    }
+
+    let eureka = tokio::spawn(eureka::run_loop(leads));
+    let ocean = tokio::spawn(ocean::run_loop(tiers, targets, stops));
+    // Without a watchlist, every node would alert several times an hour
+    let gathering = match watchlist.is_empty() {
+        true => None,
+        false => Some(tokio::spawn(gathering::run_loop(watchlist, node_lead))),
+    };
     let (eureka, ocean) = futures::join!(eureka, ocean);
     eureka?;
     ocean?;
+    if let Some(gathering) = gathering {
+        gathering.await?;
+    }
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Reads a comma-separated setting with one value for each ocean fishing stop
+fn per_stop<T: std::str::FromStr + Copy>(key: &str) -> Option<[T; 3]> {
+    let list = env::var(key).ok()?;
+    let values: Option<Vec<T>> = list.split(',').map(|value| value.trim().parse().ok()).collect();
+
+    match values.as_deref() {
+        Some(&[first, second, third]) => Some([first, second, third]),
+        _ => {
+            warn!("invalid {key} `{list}`, expected three values");
+            None
+        }
+    }
+}
@@ -1,9 +1,8 @@
-use super::APP_ID;
+use super::notification;
 use eureka_notify::{prelude::*, ocean::*};
 use chrono::{DateTime, Duration, TimeZone, Utc};
 use tokio::time::sleep;
 use tracing::*;
-use notify_rust::Notification;
 use chrono_humanize::HumanTime;
 
 enum TimeSleep {
@@ -13,7 +12,23 @@ enum TimeSleep {
     LastCall,
 }
 
-pub async fn run_loop() {
+/// Which stops to alert for during a voyage, and when they happen
+#[derive(Copy, Clone, Debug)]
+pub struct StopAlerts {
+    pub enabled: [bool; 3],
+    pub timings: VoyageTimings,
+}
+
+fn is_notable(route: Route, tiers: &TierConfig, targets: &[String]) -> bool {
+    // Subscribed targets take priority over the route tiers
+    if !targets.is_empty() {
+        return targets.iter().any(|target| route.has_target(target));
+    }
+
+    tiers.is_notable(route)
+}
+
+pub async fn run_loop(tiers: TierConfig, targets: Vec<String>, stops: StopAlerts) {
     info!("Starting ocean loop");
 
     let mut now = Utc::now().truncated(Duration::hours(2));
@@ -21,12 +36,13 @@ pub async fn run_loop() {
     // Handle special case where we start between XX:00 and XX:15
     if let Ok(duration) = ((now + Duration::minutes(15)) - Utc::now()).to_std() {
         let route = Route::from_datetime(now);
-        let do_notify = BEST_ROUTES.contains(&route) || GOOD_ROUTES.contains(&route);
+        let do_notify = is_notable(route, &tiers, &targets);
 
         if do_notify {
             info!("sleep for {:?} to last-call notification", duration);
             sleep(duration).await;
             notify_os(TimeSleep::LastCall, route, now);
+            tokio::spawn(notify_stops(Voyage { route, departure: now }, stops));
         }
     }
 
@@ -34,7 +50,7 @@ pub async fn run_loop() {
         let future = now + Duration::hours(2);
 
         let route = Route::from_datetime(future);
-        let do_notify = BEST_ROUTES.contains(&route) || GOOD_ROUTES.contains(&route);
+        let do_notify = is_notable(route, &tiers, &targets);
 
         if do_notify {
             notify_os(TimeSleep::OneCycle, route, future);
@@ -63,10 +79,46 @@ pub async fn run_loop() {
             notify_os(TimeSleep::LastCall, route, future);
         }
 
+        // Stops run well into the next voyage's notices, so alert for them alongside the loop
+        if do_notify {
+            tokio::spawn(notify_stops(Voyage { route, departure: future }, stops));
+        }
+
         now = future;
     }
 }
 
+/// Alerts as each enabled stop begins and when its spectral current is likely to start
+async fn notify_stops(voyage: Voyage, stops: StopAlerts) {
+    let legs = voyage.stops_with(&stops.timings);
+
+    for (number, (stop, _)) in legs.iter().zip(stops.enabled).enumerate().filter(|(_, (_, enabled))| *enabled) {
+        let number = number + 1;
+
+        if let Ok(duration) = (stop.start - Utc::now()).to_std() {
+            info!("sleep for {:?} to stop {number} notification", duration);
+            sleep(duration).await;
+            notify_stop(voyage.route, &format!("Stop {number}: {} ({})", stop.area.to_name(), stop.period.to_name()));
+        }
+
+        if let Ok(duration) = (stop.spectral - Utc::now()).to_std() {
+            info!("sleep for {:?} to stop {number} spectral notification", duration);
+            sleep(duration).await;
+            notify_stop(voyage.route, &format!("Spectral current likely at {}", stop.area.to_name()));
+        }
+    }
+}
+
+fn notify_stop(route: Route, body: &str) {
+    info!("sending stop notification for {route:?}");
+    notification()
+        .summary(&route.to_name())
+        .body(body)
+        .sound_name("Default")
+        .show()
+        .expect("failed to open OS notification");
+}
+
 fn notify_os<Tz: TimeZone>(timesleep: TimeSleep, route: Route, dt: DateTime<Tz>) {
     info!("sending notification for {route:?}");
     let length = match timesleep {
@@ -75,9 +127,8 @@ fn notify_os<Tz: TimeZone>(timesleep: TimeSleep, route: Route, dt: DateTime<Tz>)
         TimeSleep::Now => "now".into(),
         TimeSleep::LastCall => "closes in 2 minutes".into(),
     };
-    Notification::new()
-        .app_id(APP_ID)
-        .summary(route.to_name())
+    notification()
+        .summary(&route.to_name())
         .body(&format!("{length}"))
         .sound_name("Default")
         .show()
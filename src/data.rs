@@ -4,12 +4,20 @@ use derive_more::{Deref, DerefMut, Display};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Deserializer};
 use lazy_static::lazy_static;
+use tracing::warn;
+use crate::ocean::{Route, RouteTier};
 
 // Global file references for loading in data
 lazy_static! {
     pub static ref WEATHER_NAMES: WeatherNameMap = WeatherNameMap::from_file("data/weathers.json");
     pub static ref WEATHER_RATES: WeatherRateMap = WeatherRateMap::from_file("data/weather-index.json");
     pub static ref MAP_INFO: MapInfoMap = MapInfoMap::from_file("data/map-ids.json");
+    pub static ref HUNTS: HuntMap = HuntMap::from_file("data/hunts.json");
+    pub static ref VISTAS: VistaMap = VistaMap::from_file("data/vistas.json");
+    pub static ref FISH: FishMap = FishMap::from_file("data/fish.json");
+    pub static ref NODES: NodeMap = NodeMap::from_file("data/nodes.json");
+    pub static ref OCEAN_TARGETS: OceanTargetMap = OceanTargetMap::from_file("data/ocean-targets.json");
+    pub static ref OCEAN_TIERS: OceanTierMap = OceanTierMap::from_file("data/ocean-tiers.json");
 }
 
 /// Maps weather rate patterns, corresponding to each map's `weather_rate` field
@@ -60,11 +68,143 @@ pub struct WeatherNameInner {
     pub fr: String,
 }
 
+/// Weather & time requirements within a zone
+#[derive(serde::Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct Conditions {
+    pub zone: String,
+    /// Any of these weathers, or any weather if empty
+    #[serde(default)]
+    pub weathers: Vec<String>,
+    /// Any of these weathers in the previous cycle, or any weather if empty
+    #[serde(default)]
+    #[serde(rename = "previousWeathers")]
+    pub previous_weathers: Vec<String>,
+    #[serde(default)]
+    pub bells: Option<BellRange>,
+}
+
+/// Eorzean hours from `start` up to `end`, wrapping past midnight when `end` is before `start`
+#[derive(serde::Deserialize, Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct BellRange {
+    pub start: u32,
+    pub end: u32,
+}
+
+/// S-rank hunts with weather-dependent spawn conditions
+#[derive(serde::Deserialize, Deref, DerefMut, Clone, Eq, PartialEq, Debug)]
+pub struct HuntMap(pub Vec<Hunt>);
+
+#[derive(serde::Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct Hunt {
+    pub name: String,
+    #[serde(flatten)]
+    pub conditions: Conditions,
+    /// Real-time minutes the conditions must hold before the hunt can spawn
+    #[serde(default)]
+    pub delay: i64,
+    #[serde(default)]
+    pub notes: String,
+}
+
+/// Sightseeing log entries
+#[derive(serde::Deserialize, Deref, DerefMut, Clone, Eq, PartialEq, Debug)]
+pub struct VistaMap(pub Vec<Vista>);
+
+#[derive(serde::Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct Vista {
+    pub number: usize,
+    pub name: String,
+    #[serde(flatten)]
+    pub conditions: Conditions,
+    pub emote: String,
+}
+
+/// Big fish with weather and time-dependent catch conditions
+#[derive(serde::Deserialize, Deref, DerefMut, Clone, Eq, PartialEq, Debug)]
+pub struct FishMap(pub Vec<Fish>);
+
+#[derive(serde::Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct Fish {
+    pub name: String,
+    /// Fishing hole within the zone
+    pub spot: String,
+    #[serde(flatten)]
+    pub conditions: Conditions,
+}
+
+/// Gathering nodes that only appear at certain times
+#[derive(serde::Deserialize, Deref, DerefMut, Clone, Eq, PartialEq, Debug)]
+pub struct NodeMap(pub Vec<Node>);
+
+#[derive(serde::Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct Node {
+    pub name: String,
+    pub kind: NodeKind,
+    pub job: GatheringJob,
+    pub items: Vec<String>,
+    #[serde(flatten)]
+    pub conditions: Conditions,
+}
+
+#[derive(serde::Deserialize, Display, Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum NodeKind {
+    Unspoiled,
+    Legendary,
+    Ephemeral,
+}
+
+#[derive(serde::Deserialize, Display, Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum GatheringJob {
+    Miner,
+    Botanist,
+}
+
+/// Achievements and notable catches available on each ocean fishing route
+#[derive(serde::Deserialize, Deref, DerefMut, Clone, Eq, PartialEq, Debug)]
+pub struct OceanTargetMap(pub Vec<RouteTargets>);
+
+#[derive(serde::Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct RouteTargets {
+    pub route: Route,
+    pub points: PointPotential,
+    pub targets: Vec<OceanTarget>,
+}
+
+#[derive(serde::Deserialize, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct OceanTarget {
+    pub name: String,
+    pub kind: OceanTargetKind,
+}
+
+#[derive(serde::Deserialize, Display, Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum OceanTargetKind {
+    Achievement,
+    BlueFish,
+}
+
+/// How well a route scores for points
+#[derive(serde::Deserialize, Display, Copy, Clone, Eq, PartialEq, Debug, Hash, Ord, PartialOrd)]
+pub enum PointPotential {
+    Low,
+    Medium,
+    High,
+}
+
+/// Default tier of each ocean fishing route
+#[derive(serde::Deserialize, Deref, DerefMut, Clone, Eq, PartialEq, Debug)]
+pub struct OceanTierMap(pub HashMap<Route, RouteTier>);
+
 /// Provides support for loading a Deserializable object from file
 pub trait DataMap: Sized + DeserializeOwned {
     fn from_file<P: AsRef<Path>>(path: P) -> Self {
         let data = std::fs::read_to_string(path).expect("Unable to read file");
-        serde_json::from_str(&data).expect("Unable to parse map")
+        let map: Self = serde_json::from_str(&data).expect("Unable to parse map");
+        map.validated()
+    }
+
+    /// Drops entries which could never be used
+    fn validated(self) -> Self {
+        self
     }
 }
 
@@ -72,6 +212,49 @@ impl DataMap for MapInfoMap {}
 impl DataMap for WeatherNameMap {}
 impl DataMap for WeatherRateMap {}
 
+impl DataMap for HuntMap {
+    fn validated(self) -> Self {
+        HuntMap(valid_entries(self.0, |hunt| (&hunt.name, &hunt.conditions)))
+    }
+}
+
+impl DataMap for VistaMap {
+    fn validated(self) -> Self {
+        VistaMap(valid_entries(self.0, |vista| (&vista.name, &vista.conditions)))
+    }
+}
+
+impl DataMap for FishMap {
+    fn validated(self) -> Self {
+        FishMap(valid_entries(self.0, |fish| (&fish.name, &fish.conditions)))
+    }
+}
+
+impl DataMap for NodeMap {
+    fn validated(self) -> Self {
+        NodeMap(valid_entries(self.0, |node| (&node.name, &node.conditions)))
+    }
+}
+
+impl DataMap for OceanTargetMap {}
+impl DataMap for OceanTierMap {}
+
+/// Keeps the entries whose conditions can be searched, warning about the rest
+fn valid_entries<T, F: Fn(&T) -> (&str, &Conditions)>(entries: Vec<T>, describe: F) -> Vec<T> {
+    entries.into_iter()
+        .filter(|entry| {
+            let (name, conditions) = describe(entry);
+            match conditions.problem() {
+                Some(problem) => {
+                    warn!("skipping {name}: {problem}");
+                    false
+                }
+                None => true,
+            }
+        })
+        .collect()
+}
+
 /// Auto-converts null values to Default values
 fn deserialize_null_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
     where
@@ -26,7 +26,12 @@ impl TruncateDateTime for DateTimeEorzea {
 impl DateTimeEorzea {
     /// Get the current Eorzean date & time
     pub fn now() -> Self {
-        DateTimeEorzea(NaiveDateTime::from_timestamp((Utc::now().timestamp() as f64 * EORZEA_TIME_RATIO)  as i64, 0))
+        Self::from_utc(Utc::now())
+    }
+
+    /// Converts from UTC time
+    pub fn from_utc(dt: DateTime<Utc>) -> Self {
+        DateTimeEorzea(NaiveDateTime::from_timestamp((dt.timestamp() as f64 * EORZEA_TIME_RATIO)  as i64, 0))
     }
 
     pub fn from_timestamp(timestamp: i64) -> Self {
@@ -38,6 +43,13 @@ impl DateTimeEorzea {
         Utc.from_utc_datetime(&NaiveDateTime::from_timestamp((self.timestamp() as f64 / EORZEA_TIME_RATIO)  as i64, 0))
     }
 
+    /// Finds the first time the Eorzean clock shows `hour:minute` at or after `from`
+    pub fn next_clock(from: DateTime<Utc>, hour: u32, minute: u32) -> Self {
+        let from = Self::from_utc(from);
+        let time = from.truncated(Duration::hours(24)) + Duration::hours(hour as i64) + Duration::minutes(minute as i64);
+        if time < from { time + Duration::hours(24) } else { time }
+    }
+
     /// Finds the current weather
     pub fn to_weather_rate(self) -> EorzeaWeatherRate {
         self.into()
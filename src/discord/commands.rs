@@ -1,4 +1,14 @@
+use std::collections::HashMap;
+use chrono::Duration;
+use super::slash::{update_nodes, update_vistas};
+use super::{board_embeds, et_fields, forecast_fields, hunt_fields, node_fields, reminder_line, search_fields, vista_fields, FORECAST_SIZE};
+use crate::data::{Node, Vista};
+use crate::datetime_eorzea::DateTimeEorzea;
+use crate::ocean::TierConfig;
 use crate::store::*;
+use crate::time::{format_duration, parse_durations, parse_time, TruncateDateTime, MAX_DURATION_HOURS};
+use crate::tracker::{Tracker, WeatherSearch, WEATHER_SEARCHES};
+use crate::weather::EorzeaMap;
 use tracing::*;
 use serenity::async_trait;
 use serenity::framework::standard::{
@@ -20,3 +30,434 @@ use serenity::model::prelude::*;
 use serenity::prelude::*;
 use serenity::utils::{content_safe, ContentSafeOptions};
 use pickledb::{PickleDb, PickleDbDumpPolicy, SerializationMethod};
+
+#[command]
+#[description = "Lists your DM reminders"]
+#[bucket = "ross"]
+#[sub_commands(remind_me, remind_cancel)]
+pub async fn remind(ctx: &Context, msg: &Message) -> CommandResult {
+    let reminders = {
+        let data = ctx.data.read().await;
+        let db = data.get::<DataStore>().unwrap().lock().await;
+        let reminders = db.get::<HashMap<u64, Vec<Reminder>>>("reminders").unwrap_or_default();
+        reminders.get(&msg.author.id.0).cloned().unwrap_or_default()
+    };
+
+    if reminders.is_empty() {
+        msg.reply(&ctx.http, "You have no reminders").await?;
+        return Ok(());
+    }
+
+    let lines: Vec<String> = reminders.iter()
+        .map(reminder_line)
+        .collect();
+    msg.reply(&ctx.http, lines.join("\n")).await?;
+
+    Ok(())
+}
+
+#[command("me")]
+#[description("Sends you a DM ahead of a tracker's windows, 5 minutes before unless given lead times such as `30m 10m 2m`")]
+#[usage = "<tracker> [lead times]"]
+pub async fn remind_me(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let (name, leads) = args.rest().trim().split_once(char::is_whitespace).unwrap_or((args.rest().trim(), ""));
+    let leads = match parse_durations(leads) {
+        Some(leads) if leads.is_empty() => vec![Duration::minutes(5)],
+        Some(leads) => leads,
+        None => {
+            msg.reply(&ctx.http, format!("Could not read `{}` as lead times of up to {MAX_DURATION_HOURS}h", leads.trim())).await?;
+            return Ok(());
+        }
+    };
+
+    let tracker = match Tracker::from_name(name) {
+        Some(tracker) if tracker.next_start(chrono::Utc::now(), &TierConfig::default()).is_some() => tracker,
+        Some(tracker) => {
+            msg.reply(&ctx.http, format!("{} has no windows to be reminded about", tracker.to_name())).await?;
+            return Ok(());
+        }
+        None => {
+            msg.reply(&ctx.http, format!("Could not find tracker `{name}`")).await?;
+            return Ok(());
+        }
+    };
+
+    let mut data = ctx.data.write().await;
+    let mut db = data.get_mut::<DataStore>().unwrap().lock().await;
+    let mut reminders = db.get::<HashMap<u64, Vec<Reminder>>>("reminders").unwrap_or_default();
+    let list = reminders.entry(msg.author.id.0).or_default();
+
+    for lead in &leads {
+        if !list.iter().any(|reminder| reminder.tracker == tracker && reminder.lead() == Some(*lead)) {
+            list.push(Reminder::new(tracker, *lead));
+        }
+    }
+
+    db.set("reminders", &reminders).unwrap();
+    db.dump().expect("failed to save db");
+
+    let leads: Vec<String> = leads.into_iter().map(format_duration).collect();
+    msg.reply(&ctx.http, format!("I'll DM you {} before {}", leads.join(", "), tracker.to_name())).await?;
+
+    Ok(())
+}
+
+#[command("cancel")]
+#[aliases("remove")]
+#[description("Cancels your reminders for a tracker, or all of them")]
+#[usage = "[tracker]"]
+pub async fn remind_cancel(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let name = args.rest().trim();
+    let tracker = match name {
+        "" => None,
+        _ => match Tracker::from_name(name) {
+            Some(tracker) => Some(tracker),
+            None => {
+                msg.reply(&ctx.http, format!("Could not find tracker `{name}`")).await?;
+                return Ok(());
+            }
+        },
+    };
+
+    let mut data = ctx.data.write().await;
+    let mut db = data.get_mut::<DataStore>().unwrap().lock().await;
+    let mut reminders = db.get::<HashMap<u64, Vec<Reminder>>>("reminders").unwrap_or_default();
+
+    let mut cancelled = false;
+    if let Some(list) = reminders.get_mut(&msg.author.id.0) {
+        let before = list.len();
+        list.retain(|reminder| tracker.is_some_and(|tracker| reminder.tracker != tracker));
+        cancelled = list.len() < before;
+    }
+
+    db.set("reminders", &reminders).unwrap();
+    db.dump().expect("failed to save db");
+
+    match (tracker, cancelled) {
+        (Some(tracker), true) => msg.reply(&ctx.http, format!("Cancelled your reminders for {}", tracker.to_name())).await?,
+        (Some(tracker), false) => msg.reply(&ctx.http, format!("You have no reminders for {}", tracker.to_name())).await?,
+        (None, true) => msg.reply(&ctx.http, "Cancelled all your reminders").await?,
+        (None, false) => msg.reply(&ctx.http, "You have no reminders").await?,
+    };
+
+    Ok(())
+}
+
+#[command]
+#[description = "Lists the upcoming weather for any zone"]
+#[usage = "<zone> [count]"]
+#[bucket = "ross"]
+#[only_in(guilds)]
+pub async fn forecast(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let rest = args.rest().trim();
+    let (name, count) = rest.rsplit_once(char::is_whitespace)
+        .and_then(|(name, count)| Some((name, count.parse::<usize>().ok()?)))
+        .unwrap_or((rest, FORECAST_SIZE));
+
+    let zone = match EorzeaMap::find(name) {
+        Some(zone) => zone,
+        None => {
+            msg.reply(&ctx.http, format!("Could not find zone `{name}`")).await?;
+            return Ok(());
+        }
+    };
+
+    let fields = forecast_fields(&zone, DateTimeEorzea::now(), count.clamp(1, 25));
+    msg.channel_id.send_message(&ctx.http, |m| {
+        m.reference_message(msg).add_embed(|e| e.title(&zone).fields(fields))
+    }).await?;
+
+    Ok(())
+}
+
+#[command]
+#[description = "Shows the next and previous windows for Crab, Cassie, Skoll, Hotbox or Offensive"]
+#[usage = "<window>"]
+#[bucket = "ross"]
+#[only_in(guilds)]
+pub async fn next(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let search = match WeatherSearch::from_name(args.rest()) {
+        Some(search) => search,
+        None => {
+            let names: Vec<&str> = WEATHER_SEARCHES.iter().map(|search| search.name).collect();
+            msg.reply(&ctx.http, format!("Could not find `{}`, try one of {}", args.rest().trim(), names.join(", "))).await?;
+            return Ok(());
+        }
+    };
+
+    let fields = search_fields(search, DateTimeEorzea::now());
+    if fields.is_empty() {
+        msg.reply(&ctx.http, format!("No {} windows found", search.name)).await?;
+        return Ok(());
+    }
+
+    msg.channel_id.send_message(&ctx.http, |m| {
+        m.reference_message(msg).add_embed(|e| e.fields(fields))
+    }).await?;
+
+    Ok(())
+}
+
+#[command]
+#[description = "Lists upcoming S-rank spawn windows for a hunt, every hunt in a zone, or every hunt when given nothing"]
+#[usage = "[hunt or zone]"]
+#[bucket = "ross"]
+#[only_in(guilds)]
+pub async fn hunt(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let fields = match hunt_fields(args.rest(), DateTimeEorzea::now()) {
+        Some(fields) => fields,
+        None => {
+            msg.reply(&ctx.http, format!("Could not find a hunt or zone `{}`", args.rest().trim())).await?;
+            return Ok(());
+        }
+    };
+
+    if fields.is_empty() {
+        msg.reply(&ctx.http, "No spawn windows found").await?;
+        return Ok(());
+    }
+
+    msg.channel_id.send_message(&ctx.http, |m| {
+        m.reference_message(msg).add_embed(|e| e.fields(fields))
+    }).await?;
+
+    Ok(())
+}
+
+#[command]
+#[description = "Lists upcoming windows for a sightseeing log entry, or for every entry you have not completed when given nothing"]
+#[usage = "[entry]"]
+#[bucket = "ross"]
+#[only_in(guilds)]
+#[sub_commands(vista_done, vista_undo)]
+pub async fn vista(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let number = match args.rest().trim() {
+        "" => None,
+        text => match text.parse::<usize>() {
+            Ok(number) => Some(number),
+            Err(_) => {
+                msg.reply(&ctx.http, format!("Could not read `{text}` as an entry number")).await?;
+                return Ok(());
+            }
+        },
+    };
+
+    let completed = {
+        let data = ctx.data.read().await;
+        let db = data.get::<DataStore>().unwrap().lock().await;
+        let vistas = db.get::<HashMap<u64, Vec<usize>>>("vistas").unwrap_or_default();
+        vistas.get(&msg.author.id.0).cloned().unwrap_or_default()
+    };
+
+    let fields = match vista_fields(number, &completed, DateTimeEorzea::now()) {
+        Some(fields) => fields,
+        None => {
+            msg.reply(&ctx.http, format!("Could not find entry {}", args.rest().trim())).await?;
+            return Ok(());
+        }
+    };
+
+    if fields.is_empty() {
+        msg.reply(&ctx.http, "No sightseeing windows found").await?;
+        return Ok(());
+    }
+
+    msg.channel_id.send_message(&ctx.http, |m| {
+        m.reference_message(msg).add_embed(|e| e.fields(fields))
+    }).await?;
+
+    Ok(())
+}
+
+#[command("done")]
+#[description("Marks sightseeing log entries as completed, so they are left out of your list")]
+#[usage = "<entries>"]
+pub async fn vista_done(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let numbers = match vista_numbers(args.rest()) {
+        Ok(numbers) if numbers.is_empty() => {
+            msg.reply(&ctx.http, "Give the numbers of the entries to mark").await?;
+            return Ok(());
+        }
+        Ok(numbers) => numbers,
+        Err(text) => {
+            msg.reply(&ctx.http, format!("Could not find entry `{text}`")).await?;
+            return Ok(());
+        }
+    };
+
+    update_vistas(ctx, msg.author.id.0, |completed| {
+        for number in &numbers {
+            if !completed.contains(number) {
+                completed.push(*number);
+            }
+        }
+    }).await;
+
+    msg.reply(&ctx.http, format!("Marked {} as completed", numbers.iter().map(|number| format!("{number:03}")).collect::<Vec<_>>().join(", "))).await?;
+
+    Ok(())
+}
+
+#[command("undo")]
+#[description("Marks sightseeing log entries as not completed")]
+#[usage = "<entries>"]
+pub async fn vista_undo(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let numbers = match vista_numbers(args.rest()) {
+        Ok(numbers) if numbers.is_empty() => {
+            msg.reply(&ctx.http, "Give the numbers of the entries to mark").await?;
+            return Ok(());
+        }
+        Ok(numbers) => numbers,
+        Err(text) => {
+            msg.reply(&ctx.http, format!("Could not find entry `{text}`")).await?;
+            return Ok(());
+        }
+    };
+
+    update_vistas(ctx, msg.author.id.0, |completed| completed.retain(|number| !numbers.contains(number))).await;
+
+    msg.reply(&ctx.http, format!("Marked {} as not completed", numbers.iter().map(|number| format!("{number:03}")).collect::<Vec<_>>().join(", "))).await?;
+
+    Ok(())
+}
+
+/// Reads whitespace-separated sightseeing log entry numbers, passing back the first that is not an entry
+fn vista_numbers(text: &str) -> Result<Vec<usize>, &str> {
+    text.split_whitespace()
+        .map(|word| word.parse().ok().and_then(Vista::from_number).map(|vista| vista.number).ok_or(word))
+        .collect()
+}
+
+#[command]
+#[description = "Lists upcoming spawns for a gathering node, or for the nodes you watch when given nothing"]
+#[usage = "[node]"]
+#[bucket = "ross"]
+#[only_in(guilds)]
+#[sub_commands(node_watch, node_unwatch)]
+pub async fn node(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let watchlist = {
+        let data = ctx.data.read().await;
+        let db = data.get::<DataStore>().unwrap().lock().await;
+        let nodes = db.get::<HashMap<u64, Vec<String>>>("nodes").unwrap_or_default();
+        nodes.get(&msg.author.id.0).cloned().unwrap_or_default()
+    };
+
+    let fields = match node_fields(args.rest(), &watchlist, chrono::Utc::now()) {
+        Some(fields) => fields,
+        None => {
+            msg.reply(&ctx.http, format!("Could not find node `{}`", args.rest().trim())).await?;
+            return Ok(());
+        }
+    };
+
+    if fields.is_empty() {
+        msg.reply(&ctx.http, "No node spawns found").await?;
+        return Ok(());
+    }
+
+    msg.channel_id.send_message(&ctx.http, |m| {
+        m.reference_message(msg).add_embed(|e| e.fields(fields))
+    }).await?;
+
+    Ok(())
+}
+
+#[command("watch")]
+#[description("Adds a gathering node to your watchlist")]
+#[usage = "<node>"]
+pub async fn node_watch(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let node = match Node::from_name(args.rest().trim()) {
+        Some(node) => node,
+        None => {
+            msg.reply(&ctx.http, format!("Could not find node `{}`", args.rest().trim())).await?;
+            return Ok(());
+        }
+    };
+
+    update_nodes(ctx, msg.author.id.0, |watchlist| {
+        if !watchlist.contains(&node.name) {
+            watchlist.push(node.name.clone());
+        }
+    }).await;
+
+    msg.reply(&ctx.http, format!("Now watching {}", node.name)).await?;
+
+    Ok(())
+}
+
+#[command("unwatch")]
+#[description("Removes a gathering node from your watchlist, or clears it")]
+#[usage = "[node]"]
+pub async fn node_unwatch(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let name = args.rest().trim();
+    if name.is_empty() {
+        update_nodes(ctx, msg.author.id.0, |watchlist| watchlist.clear()).await;
+        msg.reply(&ctx.http, "Cleared your watchlist").await?;
+        return Ok(());
+    }
+
+    let removed = update_nodes(ctx, msg.author.id.0, |watchlist| {
+        let before = watchlist.len();
+        watchlist.retain(|watched| !watched.eq_ignore_ascii_case(name));
+        watchlist.len() < before
+    }).await;
+
+    match removed {
+        true => msg.reply(&ctx.http, format!("No longer watching {name}")).await?,
+        false => msg.reply(&ctx.http, format!("You are not watching `{name}`")).await?,
+    };
+
+    Ok(())
+}
+
+#[command]
+#[description = "Shows the weather board for any moment, given as a UNIX timestamp, `<t:...>`, an ISO date such as 2024-06-01 18:00, or an Eorzean time such as `ET 18:00 tomorrow`"]
+#[usage = "<time>"]
+#[bucket = "ross"]
+#[only_in(guilds)]
+pub async fn at(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let time = match parse_time(args.rest(), chrono::Utc::now()) {
+        Some(time) => time,
+        None => {
+            msg.reply(&ctx.http, format!("Could not read `{}` as a time", args.rest().trim())).await?;
+            return Ok(());
+        }
+    };
+
+    let trackers = {
+        let data = ctx.data.read().await;
+        let db = data.get::<DataStore>().unwrap().lock().await;
+        let guilds = db.get::<HashMap<u64, GuildItem>>("guilds").unwrap_or_default();
+        guilds.get(&msg.guild_id.unwrap().0).map(|guild| guild.trackers.clone()).unwrap_or_default()
+    };
+
+    let cycle = DateTimeEorzea::from_utc(time).truncated(Duration::hours(8));
+    msg.channel_id.send_message(&ctx.http, |m| {
+        m.reference_message(msg)
+            .content(format!("Weather at <t:{}:F>", time.timestamp()))
+            .add_embeds(board_embeds(cycle, &trackers))
+    }).await?;
+
+    Ok(())
+}
+
+#[command]
+#[description = "Converts between real and Eorzean time, shows the current bell with no time given, or lists when a bell range such as `18-22` next comes around"]
+#[usage = "[time]"]
+#[bucket = "ross"]
+#[only_in(guilds)]
+pub async fn et(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let fields = match et_fields(args.rest(), chrono::Utc::now()) {
+        Some(fields) => fields,
+        None => {
+            msg.reply(&ctx.http, format!("Could not read `{}` as a time or bell range", args.rest().trim())).await?;
+            return Ok(());
+        }
+    };
+
+    msg.channel_id.send_message(&ctx.http, |m| {
+        m.reference_message(msg).add_embed(|e| e.fields(fields))
+    }).await?;
+
+    Ok(())
+}
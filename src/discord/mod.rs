@@ -1,13 +1,26 @@
 mod commands;
+mod ocean;
+mod slash;
 
-use chrono::Duration;
+use chrono::{DateTime, Duration, TimeZone, Timelike, Utc};
 pub use commands::*;
-
+pub use ocean::*;
+pub use slash::*;
+use crate::data::{BellRange, Hunt, Node, Vista};
+use crate::gathering::node_spawns;
+use crate::hunt::{all_hunt_windows, hunt_zones, zone_hunt_windows, HuntWindow};
+use crate::sightseeing::{vista_windows, VistaWindow};
 use crate::status::*;
+use crate::store::{stored_lead, Reminder};
+use crate::time::{format_duration, parse_clock, parse_time, TruncateDateTime, MAX_DURATION_HOURS};
+use crate::tracker::*;
 use crate::datetime_eorzea::DateTimeEorzea;
 use crate::weather::EorzeaMap;
+use crate::window::{weather_cycles, Window};
 use tracing::*;
+use chrono_humanize::{Accuracy, HumanTime, Tense};
 use serenity::async_trait;
+use serenity::builder::CreateEmbed;
 use serenity::framework::standard::{
     Args,
     CommandGroup,
@@ -43,55 +56,314 @@ pub async fn dispatch_error(ctx: &Context, msg: &Message, error: DispatchError,
     }
 }
 
+/// How many weather periods a forecast lists unless asked for more
+pub const FORECAST_SIZE: usize = 6;
+
+/// Upcoming weather for a zone, starting with the current period
+fn forecast_fields(zone: &EorzeaMap, now: DateTimeEorzea, count: usize) -> Vec<(String, String, bool)> {
+    weather_cycles(zone, now)
+        .take(count)
+        .map(|(cycle, weather)| (
+            weather.name,
+            format!("<t:{}:R> until <t:{}:t>", cycle.start.to_utc().timestamp(), cycle.end.to_utc().timestamp()),
+            true,
+        ))
+        .collect()
+}
+
+/// How many real-time occurrences of a bell range are listed
+pub const BELL_RANGE_SIZE: usize = 3;
+
+/// Converts between Eorzean and real time, or describes the current Eorzean time when given nothing
+/// Bell ranges such as "18-22" list their next few occurrences
+fn et_fields(text: &str, now: DateTime<Utc>) -> Option<Vec<(String, String, bool)>> {
+    let text = text.trim();
+    let et = DateTimeEorzea::from_utc(now);
+
+    if text.is_empty() {
+        let cycle = et.truncated(Duration::hours(8));
+        let next = cycle + Duration::hours(8);
+        return Some(vec![
+            (format!("ET {}", et.format("%H:%M")), format!("Bell {}", et.hour()), true),
+            (format!("Weather ET {} to {}", cycle.format("%H:%M"), next.format("%H:%M")), format!("Ends <t:{}:R>", next.to_utc().timestamp()), true),
+        ]);
+    }
+
+    if let Some(range) = BellRange::from_text(text) {
+        return Some(range.windows(et, BELL_RANGE_SIZE).iter()
+            .map(|window| (
+                format!("<t:{}:R>", window.start.to_utc().timestamp()),
+                format!("<t:{}:f> to <t:{}:t>", window.start.to_utc().timestamp(), window.end.to_utc().timestamp()),
+                false,
+            ))
+            .collect());
+    }
+
+    // A bare clock time is taken as today in UTC, before anything else reads it as a number
+    let clock = parse_clock(text).and_then(|(hour, minute)| now.date_naive().and_hms_opt(hour, minute, 0));
+    let time = match clock {
+        Some(clock) => Utc.from_utc_datetime(&clock),
+        None => parse_time(text, now)?,
+    };
+
+    // Eorzean times convert to real time, everything else to Eorzean time
+    match text.to_lowercase().starts_with("et ") {
+        true => Some(vec![(format!("<t:{}:F>", time.timestamp()), format!("<t:{}:R>", time.timestamp()), false)]),
+        false => Some(vec![(format!("ET {}", DateTimeEorzea::from_utc(time).format("%H:%M")), format!("At <t:{}:F>", time.timestamp()), false)]),
+    }
+}
+
+/// Mentions each role, separated by spaces
+pub fn role_mentions(roles: &[u64]) -> String {
+    roles.iter().map(|role_id| RoleId(*role_id).mention().to_string()).collect::<Vec<_>>().join(" ")
+}
+
+/// Describes a reminder for listing, such as "Crab 10m before"
+pub fn reminder_line(reminder: &Reminder) -> String {
+    match reminder.lead() {
+        Some(lead) => format!("{} {} before", reminder.tracker.to_name(), format_duration(lead)),
+        None => format!("{} more than {MAX_DURATION_HOURS}h before, which is never sent", reminder.tracker.to_name()),
+    }
+}
+
+/// Describes a window-ending alert for listing, from its tracker and lead in seconds
+pub fn ending_line(tracker: Tracker, lead: i64) -> String {
+    match stored_lead(lead) {
+        Some(lead) => format!("{} {} before its window ends", tracker.to_name(), format_duration(lead)),
+        None => format!("{} more than {MAX_DURATION_HOURS}h before its window ends, which is never sent", tracker.to_name()),
+    }
+}
+
+/// Describes when a window starts and how long it lasts, or when it ends if already in progress
+pub fn window_text(window: &Window, now: DateTimeEorzea) -> String {
+    if window.contains(now) {
+        format!("Now, ends <t:{}:R>", window.end.to_utc().timestamp())
+    } else {
+        let duration = HumanTime::from(window.duration()).to_text_en(Accuracy::Rough, Tense::Present);
+        format!("<t:{}:R> for {}", window.start.to_utc().timestamp(), duration)
+    }
+}
+
+/// The next and previous windows for some weather
+fn search_fields(search: &WeatherSearch, now: DateTimeEorzea) -> Vec<(String, String, bool)> {
+    let mut fields = vec![];
+    if let Some(next) = search.next(now) {
+        fields.push((format!("Next {}", search.name), format!("{}: {}", search.weather, window_text(&next, now)), false));
+    }
+    if let Some(previous) = search.previous(now) {
+        fields.push((format!("Previous {}", search.name), format!("{}: {}", search.weather, window_text(&previous, now)), false));
+    }
+    fields
+}
+
+/// How many hunt spawn windows are listed
+pub const HUNT_SIZE: usize = 6;
+
+/// Upcoming spawn windows for a single hunt, every hunt in a zone, or every hunt when given nothing
+fn hunt_fields(name: &str, now: DateTimeEorzea) -> Option<Vec<(String, String, bool)>> {
+    let name = name.trim();
+
+    let windows = match Hunt::from_name(name) {
+        Some(hunt) => hunt.windows(now, HUNT_SIZE).into_iter().map(|window| HuntWindow { hunt, window }).collect(),
+        None if name.is_empty() => all_hunt_windows(now, HUNT_SIZE),
+        None => zone_hunt_windows(hunt_zones().into_iter().find(|zone| zone.eq_ignore_ascii_case(name))?, now, HUNT_SIZE),
+    };
+
+    Some(windows.iter()
+        .map(|spawn| (
+            format!("{} ({})", spawn.hunt.name, spawn.hunt.conditions.zone),
+            format!("{}\n{}", window_text(&spawn.window, now), spawn.hunt.notes),
+            false,
+        ))
+        .collect())
+}
+
+/// How many sightseeing log windows are listed
+pub const VISTA_SIZE: usize = 6;
+
+/// Upcoming windows for a single sightseeing log entry, or the next window of every entry not yet completed
+fn vista_fields(number: Option<usize>, completed: &[usize], now: DateTimeEorzea) -> Option<Vec<(String, String, bool)>> {
+    let windows = match number {
+        Some(number) => {
+            let vista = Vista::from_number(number)?;
+            vista.windows(now, VISTA_SIZE).into_iter().map(|window| VistaWindow { vista, window }).collect()
+        }
+        None => vista_windows(completed, now, VISTA_SIZE),
+    };
+
+    Some(windows.iter()
+        .map(|entry| (
+            format!("{:03} {} ({})", entry.vista.number, entry.vista.name, entry.vista.conditions.zone),
+            format!("{}\n/{}", window_text(&entry.window, now), entry.vista.emote),
+            false,
+        ))
+        .collect())
+}
+
+/// How many gathering node spawns are listed
+pub const NODE_SIZE: usize = 6;
+
+/// Upcoming spawns for a single gathering node, or for every node on a watchlist when given nothing
+fn node_fields(name: &str, watchlist: &[String], now: DateTime<Utc>) -> Option<Vec<(String, String, bool)>> {
+    let name = name.trim();
+    let spawns = match name {
+        "" => node_spawns(watchlist, now, NODE_SIZE),
+        _ => node_spawns(&[Node::from_name(name)?.name.clone()], now, NODE_SIZE),
+    };
+
+    Some(spawns.iter()
+        .map(|spawn| {
+            let window = Window::new(DateTimeEorzea::from_utc(spawn.start), DateTimeEorzea::from_utc(spawn.end));
+            (
+                format!("{} ({})", spawn.node.name, spawn.node.conditions.zone),
+                format!("{}\n{} {}: {}", window_text(&window, DateTimeEorzea::from_utc(now)), spawn.node.kind, spawn.node.job, spawn.node.items.join(", ")),
+                false,
+            )
+        })
+        .collect())
+}
+
+/// Describes a window which is ending, or has ended, along with the next one
+fn ending_field(tracker: Tracker, end: DateTimeEorzea, ended: bool) -> Option<(String, String, bool)> {
+    let current = tracker.windows(end - Duration::hours(8), 1).into_iter().next()?;
+    let next = tracker.windows(end, 1).into_iter().next();
+
+    let verb = if ended { "ended" } else { "ends" };
+    let mut text = format!("{} {verb} <t:{}:R>", current.weather, end.to_utc().timestamp());
+    if let Some(next) = next {
+        text += &format!(", next <t:{}:R>", next.window.start.to_utc().timestamp());
+    }
+    Some((tracker.to_name(), text, false))
+}
+
+/// Alert that a tracker's window ends soon
+pub async fn notify_ending(ctx: &Context, channel_id: u64, tracker: Tracker, end: DateTimeEorzea) -> Option<u64> {
+    let field = ending_field(tracker, end, false)?;
+
+    let message = ChannelId(channel_id)
+        .send_message(&ctx, |m| m.add_embed(|e| e.fields(vec![field])))
+        .await;
+
+    match message {
+        Ok(msg) => Some(msg.id.0),
+        Err(err) => {
+            error!("Error sending ending notification: {err:?}");
+            None
+        }
+    }
+}
+
+/// Updates a window-ending alert once the window is over
+pub async fn edit_ending(ctx: &Context, channel_id: u64, id: u64, tracker: Tracker, end: DateTimeEorzea) {
+    let field = match ending_field(tracker, end, true) {
+        Some(field) => field,
+        None => return,
+    };
+
+    let result = ChannelId(channel_id).edit_message(&ctx, id, |m| m.embed(|e| e.fields(vec![field]))).await;
+    if let Err(err) = result {
+        error!("Error editing ending notification: {err:?}");
+    }
+}
+
+/// Sends a user a DM about an upcoming window, returning whether it was delivered
+pub async fn send_reminder(ctx: &Context, user_id: u64, tracker: Tracker, start: DateTime<Utc>) -> bool {
+    let channel = match UserId(user_id).create_dm_channel(&ctx).await {
+        Ok(channel) => channel,
+        Err(err) => {
+            error!("Error opening DM: {err:?}");
+            return false;
+        }
+    };
+
+    let result = channel.send_message(&ctx, |m| m.content(format!("Reminder: {} <t:{}:R>", tracker.to_name(), start.timestamp()))).await;
+    if let Err(err) = &result {
+        error!("Error sending reminder: {err:?}");
+    }
+    result.is_ok()
+}
+
 /// Attempts to delete an existing message without checking if it worked
 pub async fn delete_post(ctx: &Context, channel_id: u64, id: u64) {
     ChannelId(channel_id).delete_message(&ctx, MessageId(id)).await.ok();
 }
 
-pub async fn edit_post(ctx: &Context, channel_id: u64, id: u64, now: DateTimeEorzea) {
-    let pagos = EorzeaMap::from_name("Eureka Pagos").expect("Could not find map");
-    let pyros = EorzeaMap::from_name("Eureka Pyros").expect("Could not find map");
-    let hydatos = EorzeaMap::from_name("Eureka Hydatos").expect("Could not find map");
+/// Attempts to delete messages left behind in channels the bot no longer posts in
+pub async fn delete_posts(ctx: &Context, messages: &[(u64, u64)]) {
+    for (channel_id, id) in messages {
+        delete_post(ctx, *channel_id, *id).await;
+    }
+}
+
+/// A notorious monster's next and previous spawns
+struct NmStatus {
+    tracker: Tracker,
+    name: &'static str,
+    next: DateTimeEorzea,
+    prev: DateTimeEorzea,
+}
+
+impl NmStatus {
+    fn field(&self, inline: bool) -> (String, String, bool) {
+        (
+            format!("{} <t:{}:R>", self.name, self.next.to_utc().timestamp()),
+            format!("Prev <t:{}:R>", self.prev.to_utc().timestamp()),
+            inline,
+        )
+    }
+}
+
+/// Finds a notorious monster's spawn in the given direction, e.g. `crab_status`
+type StatusFn = fn(DateTimeEorzea, Direction) -> DateTimeEorzea;
+
+/// Looks up each notorious monster being followed
+fn nm_statuses(now: DateTimeEorzea, trackers: &Trackers) -> Vec<NmStatus> {
+    let nms: [(Tracker, &'static str, StatusFn); 3] = [
+        (Tracker::Crab, "Crab", crab_status),
+        (Tracker::Cassie, "Cassie", cassie_status),
+        (Tracker::Skoll, "Skoll", skoll_status),
+    ];
+
+    nms.into_iter()
+        .filter(|(tracker, _, _)| trackers.follows(*tracker))
+        .map(|(tracker, name, status)| NmStatus { tracker, name, next: status(now, Direction::Future), prev: status(now, Direction::Past) })
+        .collect()
+}
+
+/// Current and next weather for each Eureka zone being followed
+fn zone_fields(now: DateTimeEorzea, trackers: &Trackers) -> Vec<(String, String, bool)> {
     let future = now + Duration::hours(8);
+    let zones = [
+        (Tracker::Pagos, "Pagos", "Eureka Pagos"),
+        (Tracker::Pyros, "Pyros", "Eureka Pyros"),
+        (Tracker::Hydatos, "Hydatos", "Eureka Hydatos"),
+    ];
 
-    // We will post the next crab timer when this is crab weather *or* crab weather is next
-    let crab = crab_status(now, Direction::Future);
-    let past_crab = crab_status(now, Direction::Past);
+    zones.into_iter()
+        .filter(|(tracker, _, _)| trackers.follows(*tracker))
+        .map(|(_, name, zone)| {
+            let zone = EorzeaMap::from_name(zone).expect("Could not find map");
+            (format!("{name}: {}", zone.weather(now)), format!("Next: {}", zone.weather(future)), true)
+        })
+        .collect()
+}
 
-    let cassie = cassie_status(now, Direction::Future);
-    let past_cassie = cassie_status(now, Direction::Past);
+pub async fn edit_post(ctx: &Context, channel_id: u64, id: u64, trackers: &Trackers, now: DateTimeEorzea) {
+    let future = now + Duration::hours(8);
 
-    let skoll = skoll_status(now, Direction::Future);
-    let past_skoll = skoll_status(now, Direction::Past);
+    // We will post the NM timers when one is up *or* one is next
+    let nms = nm_statuses(now, trackers);
 
     let result = ChannelId(channel_id).edit_message(&ctx, id, |m| {
         m.content("");
-        if past_crab == now || past_cassie == now || past_skoll == now {
-            m.add_embed(|e| {
-                e
-                    .field(format!("Crab <t:{}:R>", crab.to_utc().timestamp()), format!("Prev <t:{}:R>", past_crab.to_utc().timestamp()), true)
-                    .field(format!("Cassie <t:{}:R>", cassie.to_utc().timestamp()), format!("Prev <t:{}:R>", past_cassie.to_utc().timestamp()), true)
-                    .field(format!("Skoll <t:{}:R>", skoll.to_utc().timestamp()), format!("Prev <t:{}:R>", past_skoll.to_utc().timestamp()), true)
-            });
-        } else if crab == future || cassie == future || skoll == future {
-            m.add_embed(|e| {
-                if crab == future {
-                    e.field(format!("Crab <t:{}:R>", crab.to_utc().timestamp()), format!("Prev <t:{}:R>", past_crab.to_utc().timestamp()), true);
-                }
-                if cassie == future {
-                    e.field(format!("Cassie <t:{}:R>", cassie.to_utc().timestamp()), format!("Prev <t:{}:R>", past_cassie.to_utc().timestamp()), true);
-                }
-                if skoll == future {
-                    e.field(format!("Skoll <t:{}:R>", skoll.to_utc().timestamp()), format!("Prev <t:{}:R>", past_skoll.to_utc().timestamp()), true);
-                }
-                e
-            });
+        if nms.iter().any(|nm| nm.prev == now) {
+            m.add_embed(|e| e.fields(nms.iter().map(|nm| nm.field(true))));
+        } else if nms.iter().any(|nm| nm.next == future) {
+            m.add_embed(|e| e.fields(nms.iter().filter(|nm| nm.next == future).map(|nm| nm.field(true))));
         }
         m.add_embed(|e| {
             e
-                .field(format!("Pagos: {}", pagos.weather(now)), format!("Next: {}", pagos.weather(future)), true)
-                .field(format!("Pyros: {}", pyros.weather(now)), format!("Next: {}", pyros.weather(future)), true)
-                .field(format!("Hydatos: {}", hydatos.weather(now)), format!("Next: {}", hydatos.weather(future)), true)
+                .fields(zone_fields(now, trackers))
                 .field(format!("<t:{}:R>", now.to_utc().timestamp()), format!("<t:{}>", now.to_utc().timestamp()), false)
         });
         m
@@ -101,60 +373,47 @@ pub async fn edit_post(ctx: &Context, channel_id: u64, id: u64, now: DateTimeEor
     };
 }
 
-/// Create the discord log for this weather cycle
-pub async fn post_discord(ctx: &Context, channel_id: u64, role_id: Option<u64>, now: DateTimeEorzea) -> Option<u64> {
-    let pagos = EorzeaMap::from_name("Eureka Pagos").expect("Could not find map");
-    let pyros = EorzeaMap::from_name("Eureka Pyros").expect("Could not find map");
-    let hydatos = EorzeaMap::from_name("Eureka Hydatos").expect("Could not find map");
+/// The weather board for a cycle, as posted when it begins
+pub fn board_embeds(now: DateTimeEorzea, trackers: &Trackers) -> Vec<CreateEmbed> {
     let future = now + Duration::hours(8);
 
-    // We will post the next crab timer when this is crab weather *or* crab weather is next
-    let crab = crab_status(now, Direction::Future);
-    let past_crab = crab_status(now, Direction::Past);
+    // We will post the NM timers when one is up *or* one is next
+    let nms = nm_statuses(now, trackers);
+
+    let mut embeds = vec![];
+    if nms.iter().any(|nm| nm.prev == now) {
+        embeds.push(CreateEmbed::default().fields(nms.iter().map(|nm| nm.field(true))).to_owned());
+    } else if nms.iter().any(|nm| nm.next == future) {
+        embeds.push(CreateEmbed::default().fields(nms.iter().filter(|nm| nm.next == future).map(|nm| nm.field(true))).to_owned());
+    }
+    embeds.push(
+        CreateEmbed::default()
+            .fields(zone_fields(now, trackers))
+            .field(format!("Next <t:{}:R>", future.to_utc().timestamp()), format!("Started <t:{}:R>", now.to_utc().timestamp()), false)
+            .to_owned()
+    );
+    embeds
+}
+
+/// Followed trackers with windows starting next cycle
+fn upcoming_trackers(now: DateTimeEorzea, trackers: &Trackers) -> Vec<Tracker> {
+    let future = now + Duration::hours(8);
 
-    let cassie = cassie_status(now, Direction::Future);
-    let past_cassie = cassie_status(now, Direction::Past);
+    nm_statuses(now, trackers).iter().filter(|nm| nm.next == future).map(|nm| nm.tracker).collect()
+}
 
-    let skoll = skoll_status(now, Direction::Future);
-    let past_skoll = skoll_status(now, Direction::Past);
+/// Create the discord log for this weather cycle
+pub async fn post_discord(ctx: &Context, channel_id: u64, role_id: Option<u64>, roles: &TrackerRoles, trackers: &Trackers, now: DateTimeEorzea) -> Option<u64> {
+    // Notify when futures are near
+    let upcoming = upcoming_trackers(now, trackers);
+    let mentions = role_mentions(&roles.mentions(&upcoming, role_id));
 
     let message = ChannelId(channel_id)
         .send_message(&ctx, |m| {
-            // Notify when futures are near
-            if crab == future || cassie == future || skoll == future {
-                if let Some(role_id) = role_id {
-                    m.content(RoleId(role_id.clone()).mention());
-                }
-            }
-            if past_crab == now || past_cassie == now || past_skoll == now {
-                m.add_embed(|e| {
-                    e
-                        .field(format!("Crab <t:{}:R>", crab.to_utc().timestamp()), format!("Prev <t:{}:R>", past_crab.to_utc().timestamp()), true)
-                        .field(format!("Cassie <t:{}:R>", cassie.to_utc().timestamp()), format!("Prev <t:{}:R>", past_cassie.to_utc().timestamp()), true)
-                        .field(format!("Skoll <t:{}:R>", skoll.to_utc().timestamp()), format!("Prev <t:{}:R>", past_skoll.to_utc().timestamp()), true)
-                });
-            } else if crab == future || cassie == future || skoll == future {
-                m.add_embed(|e| {
-                    if crab == future {
-                        e.field(format!("Crab <t:{}:R>", crab.to_utc().timestamp()), format!("Prev <t:{}:R>", past_crab.to_utc().timestamp()), true);
-                    }
-                    if cassie == future {
-                        e.field(format!("Cassie <t:{}:R>", cassie.to_utc().timestamp()), format!("Prev <t:{}:R>", past_cassie.to_utc().timestamp()), true);
-                    }
-                    if skoll == future {
-                        e.field(format!("Skoll <t:{}:R>", skoll.to_utc().timestamp()), format!("Prev <t:{}:R>", past_skoll.to_utc().timestamp()), true);
-                    }
-                    e
-                });
+            if !mentions.is_empty() {
+                m.content(&mentions);
             }
-            m.add_embed(|e| {
-                e
-                    .field(format!("Pagos: {}", pagos.weather(now)), format!("Next: {}", pagos.weather(future)), true)
-                    .field(format!("Pyros: {}", pyros.weather(now)), format!("Next: {}", pyros.weather(future)), true)
-                    .field(format!("Hydatos: {}", hydatos.weather(now)), format!("Next: {}", hydatos.weather(future)), true)
-                    .field(format!("Next <t:{}:R>", future.to_utc().timestamp()), format!("Started <t:{}:R>", now.to_utc().timestamp()), false)
-            });
-            m
+            m.add_embeds(board_embeds(now, trackers))
         })
         .await;
 
@@ -167,65 +426,109 @@ pub async fn post_discord(ctx: &Context, channel_id: u64, role_id: Option<u64>,
     }
 }
 
-pub async fn edit_notification(ctx: &Context, channel_id: u64, id: u64, now: DateTimeEorzea) {
-    let crab = crab_status(now, Direction::Future);
-    let past_crab = crab_status(now, Direction::Past);
-
-    let cassie = cassie_status(now, Direction::Future);
-    let past_cassie = cassie_status(now, Direction::Past);
-
-    let skoll = skoll_status(now, Direction::Future);
-    let past_skoll = skoll_status(now, Direction::Past);
+/// Replaces the pre-alert for the cycle that just started with the windows it announced
+pub async fn edit_notification(ctx: &Context, channel_id: u64, id: u64, trackers: &Trackers, now: DateTimeEorzea) {
+    let nms = nm_statuses(now, trackers);
 
     let result = ChannelId(channel_id).edit_message(&ctx, id, |m| {
         m.content("")
-         .add_embed(|e| {
-            if crab == now {
-                e.field(format!("Crab <t:{}:R>", crab.to_utc().timestamp()), format!("Prev <t:{}:R>", past_crab.to_utc().timestamp()), false);
-            }
-            if cassie == now {
-                e.field(format!("Cassie <t:{}:R>", cassie.to_utc().timestamp()), format!("Prev <t:{}:R>", past_cassie.to_utc().timestamp()), false);
-            }
-            if skoll == now {
-                e.field(format!("Skoll <t:{}:R>", skoll.to_utc().timestamp()), format!("Prev <t:{}:R>", past_skoll.to_utc().timestamp()), false);
-            }
-            e
-        })
+         .add_embed(|e| e.fields(nms.iter().filter(|nm| nm.prev == now).map(|nm| nm.field(false))))
     }).await;
     if let Err(err) = result {
         eprintln!("Error editing discord notification: {err:?}");
     };
 }
 
-// We will post the next crab timer when this is crab weather *or* crab weather is next
-pub async fn notify_discord(ctx: &Context, channel_id: u64, role_id: Option<u64>, now: DateTimeEorzea) -> Option<u64> {
-    let crab = crab_status(now, Direction::Future);
-    let past_crab = crab_status(now, Direction::Past);
+/// Whether a live status message has to be posted and pinned again, given how editing it went
+/// A failed edit carries the HTTP status it failed with, if any
+fn status_needs_post(edit: Result<(), Option<u16>>) -> bool {
+    // Only a deleted message is replaced, so an outage doesn't leave a trail of pinned boards
+    matches!(edit, Err(Some(404)))
+}
+
+/// Edits a channel's live status message for this weather cycle, posting and pinning a new one if it was deleted
+/// Returns the ID of the status message
+pub async fn update_status(ctx: &Context, channel_id: u64, status_id: Option<u64>, trackers: &Trackers, now: DateTimeEorzea) -> Option<u64> {
+    let embeds = board_embeds(now, trackers);
+
+    if let Some(id) = status_id {
+        let result = ChannelId(channel_id).edit_message(&ctx, id, |m| m.content("").set_embeds(embeds.clone())).await;
+        let edit = match &result {
+            Ok(_) => Ok(()),
+            Err(SerenityError::Http(err)) => Err(err.status_code().map(|code| code.as_u16())),
+            Err(_) => Err(None),
+        };
+        match result {
+            _ if status_needs_post(edit) => info!("Status message {id} was deleted, posting a new one"),
+            Ok(_) => return Some(id),
+            Err(err) => {
+                error!("Error editing status message: {err:?}");
+                return Some(id);
+            }
+        }
+    }
+
+    let message = ChannelId(channel_id).send_message(&ctx, |m| m.set_embeds(embeds)).await;
+    match message {
+        Ok(msg) => {
+            if let Err(err) = msg.pin(&ctx).await {
+                error!("Error pinning status message: {err:?}");
+            }
+            Some(msg.id.0)
+        }
+        Err(err) => {
+            error!("Error sending status message: {err:?}");
+            None
+        }
+    }
+}
+
+/// The ping for followed windows starting next cycle, or None if no role is to be pinged
+fn ping_text(now: DateTimeEorzea, role_id: Option<u64>, roles: &TrackerRoles, trackers: &Trackers) -> Option<String> {
+    let upcoming = upcoming_trackers(now, trackers);
+    let mentions = role_mentions(&roles.mentions(&upcoming, role_id));
+    if mentions.is_empty() {
+        return None;
+    }
+
+    let names: Vec<String> = upcoming.iter().map(|tracker| tracker.to_name()).collect();
+    let future = now + Duration::hours(8);
+    Some(format!("{mentions} {} <t:{}:R>", names.join(", "), future.to_utc().timestamp()))
+}
+
+/// Pings for followed windows starting next cycle, to go alongside a live status message
+/// Unlike alerts, pings are deleted on the next tick rather than edited, since the status message already shows the window
+pub async fn ping_upcoming(ctx: &Context, channel_id: u64, role_id: Option<u64>, roles: &TrackerRoles, trackers: &Trackers, now: DateTimeEorzea) -> Option<u64> {
+    let text = ping_text(now, role_id, roles, trackers)?;
+    let message = ChannelId(channel_id).send_message(&ctx, |m| m.content(text)).await;
+
+    match message {
+        Ok(msg) => Some(msg.id.0),
+        Err(err) => {
+            error!("Error sending ping: {err:?}");
+            None
+        }
+    }
+}
+
+/// Pre-alert for followed windows starting next cycle, sending nothing if none are
+pub async fn notify_discord(ctx: &Context, channel_id: u64, role_id: Option<u64>, roles: &TrackerRoles, trackers: &Trackers, now: DateTimeEorzea) -> Option<u64> {
+    let future = now + Duration::hours(8);
 
-    let cassie = cassie_status(now, Direction::Future);
-    let past_cassie = cassie_status(now, Direction::Past);
+    let nms: Vec<NmStatus> = nm_statuses(now, trackers).into_iter().filter(|nm| nm.next == future).collect();
+    if nms.is_empty() {
+        return None;
+    }
 
-    let skoll = skoll_status(now, Direction::Future);
-    let past_skoll = skoll_status(now, Direction::Past);
+    let upcoming: Vec<Tracker> = nms.iter().map(|nm| nm.tracker).collect();
+    let mentions = role_mentions(&roles.mentions(&upcoming, role_id));
 
     let message = ChannelId(channel_id)
         .send_message(&ctx, |m| {
-            if let Some(role_id) = role_id {
-                m.content(RoleId(role_id.clone()).mention());
+            if !mentions.is_empty() {
+                m.content(&mentions);
             }
-            m.add_embed(|e| {
-                if crab == now {
-                    e.field(format!("Crab <t:{}:R>", crab.to_utc().timestamp()), format!("Prev <t:{}:R>", past_crab.to_utc().timestamp()), false);
-                }
-                if cassie == now {
-                    e.field(format!("Cassie <t:{}:R>", cassie.to_utc().timestamp()), format!("Prev <t:{}:R>", past_cassie.to_utc().timestamp()), false);
-                }
-                if skoll == now {
-                    e.field(format!("Skoll <t:{}:R>", skoll.to_utc().timestamp()), format!("Prev <t:{}:R>", past_skoll.to_utc().timestamp()), false);
-                }
-                e
-            });
-            m
+            m.add_embed(|e| e.fields(nms.iter().map(|nm| nm.field(false))))
         })
         .await;
 
@@ -237,3 +540,42 @@ pub async fn notify_discord(ctx: &Context, channel_id: u64, role_id: Option<u64>
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crab_cycle() -> DateTimeEorzea {
+        let now = DateTimeEorzea::from_utc("2024-01-01T00:00:00Z".parse().unwrap());
+        crab_status(now, Direction::Future) - Duration::hours(8)
+    }
+
+    #[test]
+    fn ping_names_upcoming_windows() {
+        let now = crab_cycle();
+        let future = (now + Duration::hours(8)).to_utc().timestamp();
+        let trackers = Trackers(vec![Tracker::Crab]);
+
+        assert_eq!(ping_text(now, Some(1), &TrackerRoles::default(), &trackers), Some(format!("<@&1> Crab <t:{future}:R>")));
+    }
+
+    #[test]
+    fn ping_needs_a_role() {
+        let trackers = Trackers(vec![Tracker::Crab]);
+        assert_eq!(ping_text(crab_cycle(), None, &TrackerRoles::default(), &trackers), None);
+    }
+
+    #[test]
+    fn ping_skips_quiet_cycles() {
+        let trackers = Trackers(vec![Tracker::Crab]);
+        assert_eq!(ping_text(crab_cycle() + Duration::hours(8), Some(1), &TrackerRoles::default(), &trackers), None);
+    }
+
+    #[test]
+    fn status_only_reposted_once_deleted() {
+        assert!(!status_needs_post(Ok(())));
+        assert!(status_needs_post(Err(Some(404))));
+        assert!(!status_needs_post(Err(Some(500))));
+        assert!(!status_needs_post(Err(None)));
+    }
+}
@@ -0,0 +1,87 @@
+use chrono::{DateTime, Duration, Utc};
+use crate::ocean::*;
+use crate::tracker::{TrackerRoles, Trackers};
+use tracing::*;
+use serenity::model::id::ChannelId;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+/// How many voyages the ocean fishing board lists
+pub const OCEAN_BOARD_SIZE: usize = 6;
+
+/// How far ahead the board looks for followed routes
+pub const OCEAN_BOARD_DAYS: i64 = 3;
+
+/// How many minutes before departure the ocean fishing role is pinged
+pub const OCEAN_PING_MINUTES: i64 = 15;
+
+fn voyage_field(voyage: &Voyage, tiers: &TierConfig) -> (String, String, bool) {
+    let tier = tiers.tier(voyage.route).map_or("Unrated", |tier| tier.to_name());
+    let destination = voyage.route.areas()[2];
+    (
+        format!("{} <t:{}:R>", voyage.route.to_name(), voyage.departure.timestamp()),
+        format!("{tier}: {} ({})", destination.to_name(), voyage.route.period().to_name()),
+        false,
+    )
+}
+
+/// Describes which voyages a guild is pinged for
+pub fn tier_lines(tiers: &TierConfig) -> Vec<String> {
+    let mut overrides: Vec<(&Route, &RouteTier)> = tiers.overrides.iter().collect();
+    overrides.sort_by_key(|(route, _)| format!("{route:?}"));
+
+    let mut lines = vec![format!("Pinging for {} routes and better", tiers.threshold.to_name())];
+    lines.extend(overrides.into_iter().map(|(route, tier)| format!("Treating {route:?} as {}", tier.to_name())));
+    lines
+}
+
+/// Post the upcoming followed voyages, starting with the one boarding at `now`
+pub async fn post_ocean(ctx: &Context, channel_id: u64, trackers: &Trackers, tiers: &TierConfig, now: DateTime<Utc>) -> Option<u64> {
+    let voyages: Vec<Voyage> = Route::schedule(now, now + Duration::days(OCEAN_BOARD_DAYS))
+        .into_iter()
+        .filter(|voyage| trackers.follows_route(voyage.route))
+        .take(OCEAN_BOARD_SIZE)
+        .collect();
+
+    if voyages.is_empty() {
+        return None;
+    }
+
+    let message = ChannelId(channel_id)
+        .send_message(&ctx, |m| {
+            m.add_embed(|e| {
+                e.title("Ocean Fishing").fields(voyages.iter().map(|voyage| voyage_field(voyage, tiers)))
+            })
+        })
+        .await;
+
+    match message {
+        Ok(msg) => Some(msg.id.0),
+        Err(err) => {
+            error!("Error sending ocean board: {err:?}");
+            None
+        }
+    }
+}
+
+/// Ping ahead of a voyage
+pub async fn notify_ocean(ctx: &Context, channel_id: u64, role_id: Option<u64>, roles: &TrackerRoles, tiers: &TierConfig, voyage: &Voyage) -> Option<u64> {
+    let role_id = roles.route_mention(voyage.route, role_id);
+
+    let message = ChannelId(channel_id)
+        .send_message(&ctx, |m| {
+            if let Some(role_id) = role_id {
+                m.content(RoleId(role_id).mention());
+            }
+            m.add_embed(|e| e.fields(vec![voyage_field(voyage, tiers)]))
+        })
+        .await;
+
+    match message {
+        Ok(msg) => Some(msg.id.0),
+        Err(err) => {
+            error!("Error sending ocean notification: {err:?}");
+            None
+        }
+    }
+}
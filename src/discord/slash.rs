@@ -0,0 +1,931 @@
+use std::collections::HashMap;
+use chrono::{Duration, Utc};
+use super::{board_embeds, delete_posts, et_fields, forecast_fields, hunt_fields, node_fields, ending_line, reminder_line, search_fields, tier_lines, vista_fields, FORECAST_SIZE};
+use crate::data::{Node, Vista, HUNTS, NODES};
+use crate::hunt::hunt_zones;
+use crate::ocean::{Route, RouteTier, TierConfig};
+use crate::datetime_eorzea::DateTimeEorzea;
+use crate::store::*;
+use crate::time::{format_duration, parse_duration, parse_durations, parse_time, TruncateDateTime, MAX_DURATION_HOURS};
+use crate::tracker::{Tracker, WeatherSearch, WEATHER_SEARCHES};
+use crate::weather::EorzeaMap;
+use tracing::*;
+use serenity::builder::{CreateApplicationCommand, CreateApplicationCommandOption, CreateEmbed};
+use serenity::model::application::command::{Command, CommandOptionType};
+use serenity::model::application::interaction::{Interaction, InteractionResponseType};
+use serenity::model::application::interaction::application_command::{ApplicationCommandInteraction, CommandDataOption, CommandDataOptionValue};
+use serenity::model::application::interaction::autocomplete::AutocompleteInteraction;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+/// Discord only shows this many autocomplete choices
+const MAX_CHOICES: usize = 25;
+
+/// What to send back for a slash command
+enum Reply {
+    Text(String),
+    Fields(Vec<(String, String, bool)>),
+    Embeds(String, Vec<CreateEmbed>),
+}
+
+/// Replaces the bot's slash commands with the current set
+pub async fn register_commands(ctx: &Context) {
+    let result = Command::set_global_application_commands(&ctx.http, |commands| {
+        commands
+            .create_application_command(notify_command)
+            .create_application_command(ping_command)
+            .create_application_command(ocean_command)
+            .create_application_command(channel_command)
+            .create_application_command(track_command)
+            .create_application_command(ending_command)
+            .create_application_command(forecast_command)
+            .create_application_command(next_command)
+            .create_application_command(hunt_command)
+            .create_application_command(vista_command)
+            .create_application_command(node_command)
+            .create_application_command(at_command)
+            .create_application_command(et_command)
+            .create_application_command(remind_command)
+    }).await;
+
+    if let Err(err) = result {
+        error!("Error registering slash commands: {err:?}");
+    }
+}
+
+fn notify_command(c: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    c.name("notify")
+        .description("Configures where the bot posts Eureka updates")
+        .default_member_permissions(Permissions::ADMINISTRATOR)
+        .dm_permission(false)
+        .create_option(|o| o.name("show").description("Explains the current notification configuration").kind(CommandOptionType::SubCommand))
+        .create_option(|o| {
+            o.name("set").description("Sets the channel to post updates").kind(CommandOptionType::SubCommand)
+                .create_sub_option(|s| s.name("channel").description("Defaults to this channel").kind(CommandOptionType::Channel).channel_types(&[ChannelType::Text]))
+        })
+        .create_option(|o| o.name("stop").description("Stops the bot from posting updates").kind(CommandOptionType::SubCommand))
+        .create_option(|o| {
+            o.name("lead").description("Sets how long before each Crab, Cassie or Skoll window to send alerts").kind(CommandOptionType::SubCommand)
+                .create_sub_option(|s| s.name("times").description("Lead times such as `30m 10m 2m`. Defaults to 5m").kind(CommandOptionType::String))
+        })
+}
+
+fn ping_command(c: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    c.name("ping")
+        .description("Configures who the bot pings for Eureka updates")
+        .default_member_permissions(Permissions::ADMINISTRATOR)
+        .dm_permission(false)
+        .create_option(|o| o.name("show").description("Explains the current mention configuration").kind(CommandOptionType::SubCommand))
+        .create_option(|o| {
+            o.name("set").description("Sets the role to ping").kind(CommandOptionType::SubCommand)
+                .create_sub_option(|s| s.name("role").description("Role to ping").kind(CommandOptionType::Role).required(true))
+        })
+        .create_option(|o| o.name("stop").description("Stops the bot from pinging anyone").kind(CommandOptionType::SubCommand))
+        .create_option(|o| {
+            o.name("for").description("Sets the role to ping for a single tracker instead of the server-wide role").kind(CommandOptionType::SubCommand)
+                .create_sub_option(|s| s.name("tracker").description("Tracker to ping for").kind(CommandOptionType::String).set_autocomplete(true).required(true))
+                .create_sub_option(|s| s.name("role").description("Role to ping").kind(CommandOptionType::Role).required(true))
+        })
+        .create_option(|o| {
+            o.name("unset").description("Pings the server-wide role for a tracker again").kind(CommandOptionType::SubCommand)
+                .create_sub_option(|s| s.name("tracker").description("Tracker to reset").kind(CommandOptionType::String).set_autocomplete(true).required(true))
+        })
+}
+
+fn ocean_command(c: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    c.name("ocean")
+        .description("Configures where the bot posts ocean fishing voyages")
+        .default_member_permissions(Permissions::ADMINISTRATOR)
+        .dm_permission(false)
+        .create_option(|o| o.name("show").description("Explains the current ocean fishing configuration").kind(CommandOptionType::SubCommand))
+        .create_option(|o| {
+            o.name("set").description("Sets the channel to post ocean fishing voyages").kind(CommandOptionType::SubCommand)
+                .create_sub_option(|s| s.name("channel").description("Defaults to this channel").kind(CommandOptionType::Channel).channel_types(&[ChannelType::Text]))
+        })
+        .create_option(|o| o.name("stop").description("Stops the bot from posting ocean fishing voyages").kind(CommandOptionType::SubCommand))
+        .create_option(|o| {
+            o.name("ping").description("Sets the role to ping before notable voyages").kind(CommandOptionType::SubCommand)
+                .create_sub_option(|s| s.name("role").description("Role to ping").kind(CommandOptionType::Role).required(true))
+        })
+        .create_option(|o| o.name("unping").description("Stops the bot from pinging before notable voyages").kind(CommandOptionType::SubCommand))
+        .create_option(|o| {
+            o.name("tier").description("Sets the lowest route tier worth pinging for").kind(CommandOptionType::SubCommand)
+                .create_sub_option(|s| tier_option(s).required(true))
+        })
+        .create_option(|o| {
+            o.name("override").description("Changes the tier of a single route").kind(CommandOptionType::SubCommand)
+                .create_sub_option(|s| s.name("route").description("Ocean fishing route").kind(CommandOptionType::String).set_autocomplete(true).required(true))
+                .create_sub_option(|s| tier_option(s).description("Defaults to the route's usual tier"))
+        })
+}
+
+fn tier_option(o: &mut CreateApplicationCommandOption) -> &mut CreateApplicationCommandOption {
+    o.name("tier").description("Route tier").kind(CommandOptionType::String);
+    for tier in [RouteTier::Bad, RouteTier::Good, RouteTier::VeryGood, RouteTier::Best] {
+        o.add_string_choice(tier.to_name(), tier.to_name());
+    }
+    o
+}
+
+fn channel_command(c: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    c.name("channel")
+        .description("Chooses what the bot posts in each channel")
+        .default_member_permissions(Permissions::ADMINISTRATOR)
+        .dm_permission(false)
+        .create_option(|o| o.name("show").description("Lists every channel the bot posts in and what it posts there").kind(CommandOptionType::SubCommand))
+        .create_option(|o| {
+            o.name("add").description("Posts the given content in a channel").kind(CommandOptionType::SubCommand)
+                .create_sub_option(|s| content_option(s).required(true))
+                .create_sub_option(|s| s.name("channel").description("Defaults to this channel").kind(CommandOptionType::Channel).channel_types(&[ChannelType::Text]))
+        })
+        .create_option(|o| {
+            o.name("remove").description("Stops posting the given content in a channel, or everything if none is given").kind(CommandOptionType::SubCommand)
+                .create_sub_option(content_option)
+                .create_sub_option(|s| s.name("channel").description("Defaults to this channel").kind(CommandOptionType::Channel).channel_types(&[ChannelType::Text]))
+        })
+}
+
+fn content_option(o: &mut CreateApplicationCommandOption) -> &mut CreateApplicationCommandOption {
+    o.name("content").description("What to post").kind(CommandOptionType::String);
+    for content in ChannelContent::ALL {
+        o.add_string_choice(content.to_name(), content.to_name());
+    }
+    o
+}
+
+fn track_command(c: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    c.name("track")
+        .description("Chooses what the bot posts about")
+        .default_member_permissions(Permissions::ADMINISTRATOR)
+        .dm_permission(false)
+        .create_option(|o| o.name("show").description("Lists the trackers this server follows").kind(CommandOptionType::SubCommand))
+        .create_option(|o| {
+            o.name("add").description("Follows a tracker, such as Crab, Pagos or an ocean route").kind(CommandOptionType::SubCommand)
+                .create_sub_option(|s| s.name("tracker").description("Tracker to follow").kind(CommandOptionType::String).set_autocomplete(true).required(true))
+        })
+        .create_option(|o| {
+            o.name("remove").description("Stops following a tracker").kind(CommandOptionType::SubCommand)
+                .create_sub_option(|s| s.name("tracker").description("Tracker to stop following").kind(CommandOptionType::String).set_autocomplete(true).required(true))
+        })
+}
+
+fn ending_command(c: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    c.name("ending")
+        .description("Chooses which trackers get alerts before their windows end")
+        .default_member_permissions(Permissions::ADMINISTRATOR)
+        .dm_permission(false)
+        .create_option(|o| o.name("show").description("Lists the trackers which get alerts before their windows end").kind(CommandOptionType::SubCommand))
+        .create_option(|o| {
+            o.name("add").description("Alerts before a tracker's windows end").kind(CommandOptionType::SubCommand)
+                .create_sub_option(|s| s.name("tracker").description("Tracker to alert for").kind(CommandOptionType::String).set_autocomplete(true).required(true))
+                .create_sub_option(|s| s.name("lead").description("How long before the window ends, such as 5m. Defaults to 10m").kind(CommandOptionType::String))
+        })
+        .create_option(|o| {
+            o.name("remove").description("Stops alerting before a tracker's windows end").kind(CommandOptionType::SubCommand)
+                .create_sub_option(|s| s.name("tracker").description("Tracker to stop alerting for").kind(CommandOptionType::String).set_autocomplete(true).required(true))
+        })
+}
+
+fn forecast_command(c: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    c.name("forecast")
+        .description("Lists the upcoming weather for any zone")
+        .dm_permission(false)
+        .create_option(|o| o.name("zone").description("Zone to forecast").kind(CommandOptionType::String).set_autocomplete(true).required(true))
+        .create_option(|o| o.name("count").description("How many weather periods to list").kind(CommandOptionType::Integer).min_int_value(1).max_int_value(25))
+}
+
+fn next_command(c: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    c.name("next")
+        .description("Shows the next and previous windows for some weather")
+        .dm_permission(false)
+        .create_option(|o| {
+            o.name("window").description("Weather to look up").kind(CommandOptionType::String).required(true);
+            for search in WEATHER_SEARCHES.iter() {
+                o.add_string_choice(search.name, search.name);
+            }
+            o
+        })
+}
+
+fn hunt_command(c: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    c.name("hunt")
+        .description("Lists upcoming S-rank spawn windows")
+        .dm_permission(false)
+        .create_option(|o| o.name("hunt").description("A hunt or zone. Defaults to every hunt").kind(CommandOptionType::String).set_autocomplete(true))
+}
+
+fn vista_command(c: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    c.name("vista")
+        .description("Sightseeing log windows")
+        .dm_permission(false)
+        .create_option(|o| {
+            o.name("show").description("Lists upcoming windows for an entry, or for every entry you have not completed").kind(CommandOptionType::SubCommand)
+                .create_sub_option(|s| s.name("entry").description("Entry number. Defaults to every entry you have not completed").kind(CommandOptionType::Integer).min_int_value(1))
+        })
+        .create_option(|o| {
+            o.name("done").description("Marks an entry as completed, so it is left out of your list").kind(CommandOptionType::SubCommand)
+                .create_sub_option(|s| s.name("entry").description("Entry number").kind(CommandOptionType::Integer).min_int_value(1).required(true))
+        })
+        .create_option(|o| {
+            o.name("undo").description("Marks an entry as not completed").kind(CommandOptionType::SubCommand)
+                .create_sub_option(|s| s.name("entry").description("Entry number").kind(CommandOptionType::Integer).min_int_value(1).required(true))
+        })
+}
+
+fn node_command(c: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    c.name("node")
+        .description("Timed gathering node spawns")
+        .dm_permission(false)
+        .create_option(|o| {
+            o.name("show").description("Lists upcoming spawns for a node, or for the nodes you watch").kind(CommandOptionType::SubCommand)
+                .create_sub_option(|s| s.name("node").description("Gathering node. Defaults to your watchlist").kind(CommandOptionType::String).set_autocomplete(true))
+        })
+        .create_option(|o| {
+            o.name("watch").description("Adds a node to your watchlist").kind(CommandOptionType::SubCommand)
+                .create_sub_option(|s| s.name("node").description("Gathering node").kind(CommandOptionType::String).set_autocomplete(true).required(true))
+        })
+        .create_option(|o| {
+            o.name("unwatch").description("Removes a node from your watchlist, or clears it").kind(CommandOptionType::SubCommand)
+                .create_sub_option(|s| s.name("node").description("Gathering node. Defaults to every node").kind(CommandOptionType::String).set_autocomplete(true))
+        })
+}
+
+fn at_command(c: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    c.name("at")
+        .description("Shows the weather board for any moment")
+        .dm_permission(false)
+        .create_option(|o| o.name("time").description("A UNIX timestamp, an ISO date such as 2024-06-01 18:00, or an Eorzean time such as ET 18:00 tomorrow").kind(CommandOptionType::String).required(true))
+}
+
+fn et_command(c: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    c.name("et")
+        .description("Converts between real and Eorzean time")
+        .dm_permission(false)
+        .create_option(|o| o.name("time").description("A time such as 14:30 UTC or ET 18:00, or a bell range such as 18-22. Defaults to now").kind(CommandOptionType::String))
+}
+
+fn remind_command(c: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    c.name("remind")
+        .description("Personal DM reminders")
+        .dm_permission(true)
+        .create_option(|o| o.name("list").description("Lists your DM reminders").kind(CommandOptionType::SubCommand))
+        .create_option(|o| {
+            o.name("add").description("Sends you a DM ahead of a tracker's windows").kind(CommandOptionType::SubCommand)
+                .create_sub_option(|s| s.name("tracker").description("Tracker to be reminded about").kind(CommandOptionType::String).set_autocomplete(true).required(true))
+                .create_sub_option(|s| s.name("lead").description("How long before each window, such as `30m 10m 2m`. Defaults to 5m").kind(CommandOptionType::String))
+        })
+        .create_option(|o| {
+            o.name("cancel").description("Cancels your reminders for a tracker, or all of them").kind(CommandOptionType::SubCommand)
+                .create_sub_option(|s| s.name("tracker").description("Tracker to stop reminding about").kind(CommandOptionType::String).set_autocomplete(true))
+        })
+}
+
+/// Responds to slash commands and their autocomplete requests
+pub async fn handle_interaction(ctx: &Context, interaction: Interaction) {
+    let result = match interaction {
+        Interaction::ApplicationCommand(command) => run_command(ctx, &command).await,
+        Interaction::Autocomplete(autocomplete) => run_autocomplete(ctx, &autocomplete).await,
+        _ => Ok(()),
+    };
+
+    if let Err(err) = result {
+        error!("Error responding to interaction: {err:?}");
+    }
+}
+
+/// Splits off a command's subcommand, if it has one, from the options it was given
+fn subcommand(options: &[CommandDataOption]) -> (&str, &[CommandDataOption]) {
+    match options.first() {
+        Some(first) if first.kind == CommandOptionType::SubCommand => (first.name.as_str(), &first.options),
+        _ => ("", options),
+    }
+}
+
+fn option<'a>(options: &'a [CommandDataOption], name: &str) -> Option<&'a CommandDataOptionValue> {
+    options.iter().find(|option| option.name == name).and_then(|option| option.resolved.as_ref())
+}
+
+fn integer_option(options: &[CommandDataOption], name: &str) -> Option<i64> {
+    match option(options, name) {
+        Some(CommandDataOptionValue::Integer(value)) => Some(*value),
+        _ => None,
+    }
+}
+
+fn string_option<'a>(options: &'a [CommandDataOption], name: &str) -> Option<&'a str> {
+    match option(options, name) {
+        Some(CommandDataOptionValue::String(value)) => Some(value.as_str()),
+        _ => None,
+    }
+}
+
+fn channel_option(options: &[CommandDataOption], name: &str) -> Option<u64> {
+    match option(options, name) {
+        Some(CommandDataOptionValue::Channel(channel)) => Some(channel.id.0),
+        _ => None,
+    }
+}
+
+fn role_option(options: &[CommandDataOption], name: &str) -> Option<u64> {
+    match option(options, name) {
+        Some(CommandDataOptionValue::Role(role)) => Some(role.id.0),
+        _ => None,
+    }
+}
+
+async fn run_command(ctx: &Context, command: &ApplicationCommandInteraction) -> serenity::Result<()> {
+    let (subcommand, options) = subcommand(&command.data.options);
+    let user_id = command.user.id.0;
+    if command.guild_id.is_none() && command.data.name != "remind" {
+        return Ok(());
+    }
+
+    // Discord only waits 3 seconds for a response, and the store can be held by the main loop for longer
+    defer(ctx, command).await?;
+
+    let guild_id = match command.guild_id {
+        Some(guild_id) => guild_id.0,
+        None => return respond(ctx, command, remind_reply(ctx, user_id, subcommand, options).await).await,
+    };
+    let channel_id = channel_option(options, "channel").unwrap_or(command.channel_id.0);
+    // Messages left behind by a change, deleted once the interaction has been answered
+    let mut stale = vec![];
+
+    let reply = match (command.data.name.as_str(), subcommand) {
+        ("notify", "show") => {
+            let guild = guild_item(ctx, guild_id).await;
+            let mut lines: Vec<String> = guild.channels_with(ChannelContent::Board)
+                .map(|binding| format!("Posting in {}", ChannelId(binding.channel_id).mention()))
+                .collect();
+            match lines.is_empty() {
+                true => Reply::Text("Not currently set to post updates.\nUse `/notify set` to set a channel".into()),
+                false => {
+                    let leads: Vec<String> = guild.lead_times().into_iter().map(format_duration).collect();
+                    lines.push(format!("Alerting {} before each window", leads.join(", ")));
+                    Reply::Text(lines.join("\n"))
+                }
+            }
+        }
+        ("notify", "set") => {
+            stale = update_guild(ctx, guild_id, |guild| guild.rebind(channel_id, &[ChannelContent::Board, ChannelContent::Alerts])).await;
+            Reply::Text(format!("Will now post updates in {}", ChannelId(channel_id).mention()))
+        }
+        ("notify", "stop") => {
+            stale = update_guild(ctx, guild_id, |guild| guild.unbind(None, &[ChannelContent::Board, ChannelContent::Alerts])).await;
+            Reply::Text("No longer sending updates".into())
+        }
+        ("notify", "lead") => {
+            let text = string_option(options, "times").unwrap_or_default();
+            match parse_durations(text) {
+                Some(leads) => {
+                    update_guild(ctx, guild_id, |guild| guild.set_lead_times(&leads)).await;
+                    let leads: Vec<String> = guild_item(ctx, guild_id).await.lead_times().into_iter().map(format_duration).collect();
+                    Reply::Text(format!("Will send alerts {} before each window", leads.join(", ")))
+                }
+                None => Reply::Text(format!("Could not read `{text}` as lead times of up to {MAX_DURATION_HOURS}h")),
+            }
+        }
+        ("ping", "show") => {
+            let guild = guild_item(ctx, guild_id).await;
+            let mut lines = vec![match guild.role_id {
+                Some(role_id) => format!("Will ping {}", RoleId(role_id).mention()),
+                None => "Not currently set to ping.\nUse `/ping set` to set a role".to_string(),
+            }];
+            lines.extend(guild.roles.iter().map(|(tracker, role_id)| format!("{}: {}", tracker.to_name(), RoleId(*role_id).mention())));
+            Reply::Text(lines.join("\n"))
+        }
+        ("ping", "for") => {
+            let name = string_option(options, "tracker").unwrap_or_default();
+            match (Tracker::from_name(name), role_option(options, "role")) {
+                (Some(tracker), Some(role_id)) => {
+                    update_guild(ctx, guild_id, |guild| guild.roles.set(tracker, role_id)).await;
+                    Reply::Text(format!("Will now ping {} for {}", RoleId(role_id).mention(), tracker.to_name()))
+                }
+                (None, _) => Reply::Text(format!("Could not find tracker `{name}`")),
+                (_, None) => Reply::Text("Could not find that role".into()),
+            }
+        }
+        ("ping", "unset") => {
+            let name = string_option(options, "tracker").unwrap_or_default();
+            match Tracker::from_name(name) {
+                Some(tracker) => {
+                    update_guild(ctx, guild_id, |guild| guild.roles.unset(tracker)).await;
+                    Reply::Text(format!("No longer using a separate role for {}", tracker.to_name()))
+                }
+                None => Reply::Text(format!("Could not find tracker `{name}`")),
+            }
+        }
+        ("ping", "set") => {
+            let role_id = role_option(options, "role");
+            update_guild(ctx, guild_id, |guild| guild.role_id = role_id).await;
+            match role_id {
+                Some(role_id) => Reply::Text(format!("Will now ping {}", RoleId(role_id).mention())),
+                None => Reply::Text("Could not find that role".into()),
+            }
+        }
+        ("ping", "stop") => {
+            update_guild(ctx, guild_id, |guild| guild.role_id = None).await;
+            Reply::Text("No longer pinging anyone".into())
+        }
+        ("ocean", "show") => {
+            let guild = guild_item(ctx, guild_id).await;
+            let mut lines = vec![];
+            for binding in guild.channels_with(ChannelContent::Ocean) {
+                lines.push(format!("Posting ocean fishing in {}", ChannelId(binding.channel_id).mention()));
+            }
+            if let Some(role_id) = guild.ocean_role_id {
+                lines.push(format!("Will ping {} before notable voyages", RoleId(role_id).mention()));
+            }
+            if !lines.is_empty() {
+                lines.extend(tier_lines(&guild.tiers));
+            }
+            if lines.is_empty() {
+                Reply::Text("Not currently set to post ocean fishing.\nUse `/ocean set` to set a channel".into())
+            } else {
+                Reply::Text(lines.join("\n"))
+            }
+        }
+        ("ocean", "set") => {
+            stale = update_guild(ctx, guild_id, |guild| guild.rebind(channel_id, &[ChannelContent::Ocean, ChannelContent::OceanAlerts])).await;
+            Reply::Text(format!("Will now post ocean fishing voyages in {}", ChannelId(channel_id).mention()))
+        }
+        ("ocean", "stop") => {
+            stale = update_guild(ctx, guild_id, |guild| guild.unbind(None, &[ChannelContent::Ocean, ChannelContent::OceanAlerts])).await;
+            Reply::Text("No longer posting ocean fishing voyages".into())
+        }
+        ("ocean", "ping") => {
+            let role_id = role_option(options, "role");
+            update_guild(ctx, guild_id, |guild| guild.ocean_role_id = role_id).await;
+            match role_id {
+                Some(role_id) => Reply::Text(format!("Will now ping {} before notable voyages", RoleId(role_id).mention())),
+                None => Reply::Text("Could not find that role".into()),
+            }
+        }
+        ("ocean", "unping") => {
+            update_guild(ctx, guild_id, |guild| guild.ocean_role_id = None).await;
+            Reply::Text("No longer pinging before notable voyages".into())
+        }
+        ("ocean", "tier") => match RouteTier::from_name(string_option(options, "tier").unwrap_or_default()) {
+            Some(tier) => {
+                update_guild(ctx, guild_id, |guild| guild.tiers.threshold = tier).await;
+                Reply::Text(format!("Will ping for {} routes and better", tier.to_name()))
+            }
+            None => Reply::Text("Could not find that tier".into()),
+        },
+        ("ocean", "override") => {
+            let name = string_option(options, "route").unwrap_or_default();
+            let tier = string_option(options, "tier").and_then(RouteTier::from_name);
+            match Route::from_name(name) {
+                Some(route) => {
+                    update_guild(ctx, guild_id, |guild| match tier {
+                        Some(tier) => guild.tiers.overrides.insert(route, tier),
+                        None => guild.tiers.overrides.remove(&route),
+                    }).await;
+                    match tier {
+                        Some(tier) => Reply::Text(format!("Now treating {route:?} as {}", tier.to_name())),
+                        None => Reply::Text(format!("Using the default tier for {route:?}")),
+                    }
+                }
+                None => Reply::Text(format!("Could not find route `{name}`")),
+            }
+        }
+        ("channel", "show") => {
+            let guild = guild_item(ctx, guild_id).await;
+            let lines: Vec<String> = guild.channels.iter()
+                .map(|binding| format!("{}: {}", ChannelId(binding.channel_id).mention(), binding.content.iter().map(|content| content.to_name()).collect::<Vec<_>>().join(", ")))
+                .collect();
+            match lines.is_empty() {
+                true => Reply::Text("Not currently posting anywhere.\nUse `/channel add` to choose what to post".into()),
+                false => Reply::Text(lines.join("\n")),
+            }
+        }
+        ("channel", "add") => {
+            match string_option(options, "content").and_then(ChannelContent::from_name) {
+                Some(content) => {
+                    update_guild(ctx, guild_id, |guild| guild.bind(channel_id, &[content])).await;
+                    Reply::Text(format!("Will now post {} in {}", content.to_name(), ChannelId(channel_id).mention()))
+                }
+                None => Reply::Text("Could not find that content".into()),
+            }
+        }
+        ("channel", "remove") => {
+            let content = match string_option(options, "content").and_then(ChannelContent::from_name) {
+                Some(content) => vec![content],
+                None => ChannelContent::ALL.to_vec(),
+            };
+            stale = update_guild(ctx, guild_id, |guild| guild.unbind(Some(channel_id), &content)).await;
+            Reply::Text(format!("No longer posting {} in {}", content.iter().map(|content| content.to_name()).collect::<Vec<_>>().join(", "), ChannelId(channel_id).mention()))
+        }
+        ("track", "show") => {
+            let guild = guild_item(ctx, guild_id).await;
+            match guild.trackers.is_empty() {
+                true => Reply::Text("Following everything".into()),
+                false => Reply::Text(format!("Following {}", guild.trackers.iter().map(|tracker| tracker.to_name()).collect::<Vec<_>>().join(", "))),
+            }
+        }
+        ("track", "add") | ("track", "remove") => {
+            let name = string_option(options, "tracker").unwrap_or_default();
+            let add = subcommand == "add";
+
+            match Tracker::from_name(name) {
+                Some(tracker) => {
+                    let changed = update_guild(ctx, guild_id, |guild| match add {
+                        true => guild.trackers.follow(tracker),
+                        false => guild.trackers.unfollow(tracker),
+                    }).await;
+
+                    match (add, changed) {
+                        (true, true) => Reply::Text(format!("Now following {}", tracker.to_name())),
+                        (true, false) => Reply::Text(format!("Already following {}", tracker.to_name())),
+                        (false, true) => Reply::Text(format!("No longer following {}", tracker.to_name())),
+                        (false, false) => Reply::Text(format!("{} is the only tracker followed, add another before removing it", tracker.to_name())),
+                    }
+                }
+                None => Reply::Text(format!("Could not find tracker `{name}`")),
+            }
+        }
+        ("ending", "show") => {
+            let guild = guild_item(ctx, guild_id).await;
+            match guild.endings.is_empty() {
+                true => Reply::Text("No window-ending alerts.\nUse `/ending add` to add one".into()),
+                false => Reply::Text(guild.endings.iter()
+                    .map(|(tracker, lead)| ending_line(*tracker, *lead))
+                    .collect::<Vec<_>>()
+                    .join("\n")),
+            }
+        }
+        ("ending", "add") => {
+            let name = string_option(options, "tracker").unwrap_or_default();
+            let lead = match string_option(options, "lead") {
+                Some(text) => parse_duration(text).filter(|lead| *lead > Duration::zero()),
+                None => Some(Duration::minutes(DEFAULT_ENDING_MINUTES)),
+            };
+
+            match (Tracker::from_name(name), lead) {
+                (Some(tracker), Some(lead)) if !tracker.windows(DateTimeEorzea::now(), 1).is_empty() => {
+                    update_guild(ctx, guild_id, |guild| guild.set_ending(tracker, Some(lead))).await;
+                    Reply::Text(format!("Will alert {} before {} windows end", format_duration(lead), tracker.to_name()))
+                }
+                (Some(tracker), Some(_)) => Reply::Text(format!("{} has no windows to end", tracker.to_name())),
+                (Some(_), None) => Reply::Text(format!("Could not read `{}` as a lead time of up to {MAX_DURATION_HOURS}h", string_option(options, "lead").unwrap_or_default())),
+                (None, _) => Reply::Text(format!("Could not find tracker `{name}`")),
+            }
+        }
+        ("ending", "remove") => {
+            let name = string_option(options, "tracker").unwrap_or_default();
+            match Tracker::from_name(name) {
+                Some(tracker) => {
+                    update_guild(ctx, guild_id, |guild| guild.set_ending(tracker, None)).await;
+                    Reply::Text(format!("No longer alerting before {} windows end", tracker.to_name()))
+                }
+                None => Reply::Text(format!("Could not find tracker `{name}`")),
+            }
+        }
+        ("forecast", _) => {
+            let name = string_option(options, "zone").unwrap_or_default();
+            let count = integer_option(options, "count").map_or(FORECAST_SIZE, |count| count.clamp(1, 25) as usize);
+
+            match EorzeaMap::find(name) {
+                Some(zone) => Reply::Fields(forecast_fields(&zone, DateTimeEorzea::now(), count)),
+                None => Reply::Text(format!("Could not find zone `{name}`")),
+            }
+        }
+        ("next", _) => {
+            let name = string_option(options, "window").unwrap_or_default();
+            match WeatherSearch::from_name(name) {
+                Some(search) => match search_fields(search, DateTimeEorzea::now()) {
+                    fields if fields.is_empty() => Reply::Text(format!("No {} windows found", search.name)),
+                    fields => Reply::Fields(fields),
+                },
+                None => Reply::Text(format!("Could not find `{name}`")),
+            }
+        }
+        ("hunt", _) => {
+            let name = string_option(options, "hunt").unwrap_or_default();
+            match hunt_fields(name, DateTimeEorzea::now()) {
+                Some(fields) if fields.is_empty() => Reply::Text("No spawn windows found".into()),
+                Some(fields) => Reply::Fields(fields),
+                None => Reply::Text(format!("Could not find a hunt or zone `{name}`")),
+            }
+        }
+        ("vista", "show") => {
+            let number = integer_option(options, "entry").map(|number| number as usize);
+            let completed = {
+                let data = ctx.data.read().await;
+                let db = data.get::<DataStore>().unwrap().lock().await;
+                let vistas = db.get::<HashMap<u64, Vec<usize>>>("vistas").unwrap_or_default();
+                vistas.get(&user_id).cloned().unwrap_or_default()
+            };
+
+            match vista_fields(number, &completed, DateTimeEorzea::now()) {
+                Some(fields) if fields.is_empty() => Reply::Text("No sightseeing windows found".into()),
+                Some(fields) => Reply::Fields(fields),
+                None => Reply::Text(format!("Could not find entry {}", number.unwrap_or_default())),
+            }
+        }
+        ("vista", "done") => {
+            let number = integer_option(options, "entry").unwrap_or_default() as usize;
+            match Vista::from_number(number) {
+                Some(vista) => {
+                    update_vistas(ctx, user_id, |completed| {
+                        if !completed.contains(&vista.number) {
+                            completed.push(vista.number);
+                        }
+                    }).await;
+                    Reply::Text(format!("Marked {:03} {} as completed", vista.number, vista.name))
+                }
+                None => Reply::Text(format!("Could not find entry {number}")),
+            }
+        }
+        ("vista", "undo") => {
+            let number = integer_option(options, "entry").unwrap_or_default() as usize;
+            match Vista::from_number(number) {
+                Some(vista) => {
+                    update_vistas(ctx, user_id, |completed| completed.retain(|number| *number != vista.number)).await;
+                    Reply::Text(format!("Marked {:03} {} as not completed", vista.number, vista.name))
+                }
+                None => Reply::Text(format!("Could not find entry {number}")),
+            }
+        }
+        ("node", "show") => {
+            let name = string_option(options, "node").unwrap_or_default();
+            let watchlist = {
+                let data = ctx.data.read().await;
+                let db = data.get::<DataStore>().unwrap().lock().await;
+                let nodes = db.get::<HashMap<u64, Vec<String>>>("nodes").unwrap_or_default();
+                nodes.get(&user_id).cloned().unwrap_or_default()
+            };
+
+            match node_fields(name, &watchlist, Utc::now()) {
+                Some(fields) if fields.is_empty() => Reply::Text("No node spawns found".into()),
+                Some(fields) => Reply::Fields(fields),
+                None => Reply::Text(format!("Could not find node `{name}`")),
+            }
+        }
+        ("node", "watch") => {
+            let name = string_option(options, "node").unwrap_or_default();
+            match Node::from_name(name) {
+                Some(node) => {
+                    update_nodes(ctx, user_id, |watchlist| {
+                        if !watchlist.contains(&node.name) {
+                            watchlist.push(node.name.clone());
+                        }
+                    }).await;
+                    Reply::Text(format!("Now watching {}", node.name))
+                }
+                None => Reply::Text(format!("Could not find node `{name}`")),
+            }
+        }
+        ("node", "unwatch") => match string_option(options, "node") {
+            Some(name) => {
+                let removed = update_nodes(ctx, user_id, |watchlist| {
+                    let before = watchlist.len();
+                    watchlist.retain(|watched| !watched.eq_ignore_ascii_case(name));
+                    watchlist.len() < before
+                }).await;
+                match removed {
+                    true => Reply::Text(format!("No longer watching {name}")),
+                    false => Reply::Text(format!("You are not watching `{name}`")),
+                }
+            }
+            None => {
+                update_nodes(ctx, user_id, |watchlist| watchlist.clear()).await;
+                Reply::Text("Cleared your watchlist".into())
+            }
+        },
+        ("at", _) => {
+            let text = string_option(options, "time").unwrap_or_default();
+            match parse_time(text, Utc::now()) {
+                Some(time) => {
+                    let trackers = guild_item(ctx, guild_id).await.trackers;
+                    let cycle = DateTimeEorzea::from_utc(time).truncated(Duration::hours(8));
+                    Reply::Embeds(format!("Weather at <t:{}:F>", time.timestamp()), board_embeds(cycle, &trackers))
+                }
+                None => Reply::Text(format!("Could not read `{text}` as a time")),
+            }
+        }
+        ("et", _) => {
+            let text = string_option(options, "time").unwrap_or_default();
+            match et_fields(text, Utc::now()) {
+                Some(fields) => Reply::Fields(fields),
+                None => Reply::Text(format!("Could not read `{text}` as a time or bell range")),
+            }
+        }
+        ("remind", _) => remind_reply(ctx, user_id, subcommand, options).await,
+        _ => Reply::Text("Unknown command".into()),
+    };
+
+    respond(ctx, command, reply).await?;
+
+    // Old posts are deleted last, so the reply isn't held up by them
+    delete_posts(ctx, &stale).await;
+    Ok(())
+}
+
+/// Acknowledges a slash command straight away, showing that a reply is on its way to whoever used it
+async fn defer(ctx: &Context, command: &ApplicationCommandInteraction) -> serenity::Result<()> {
+    command.create_interaction_response(&ctx.http, |r| {
+        r.kind(InteractionResponseType::DeferredChannelMessageWithSource)
+            .interaction_response_data(|d| d.ephemeral(true))
+    }).await
+}
+
+/// Fills in the reply to a deferred slash command, visible only to whoever used it
+async fn respond(ctx: &Context, command: &ApplicationCommandInteraction, reply: Reply) -> serenity::Result<()> {
+    command.edit_original_interaction_response(&ctx.http, |d| {
+        match reply {
+            Reply::Text(text) => d.content(text),
+            Reply::Fields(fields) => d.embed(|e| e.fields(fields)),
+            Reply::Embeds(content, embeds) => d.content(content).add_embeds(embeds),
+        }
+    }).await?;
+    Ok(())
+}
+
+/// Answers the reminder commands, which belong to a user rather than a guild and so also work in DMs
+async fn remind_reply(ctx: &Context, user_id: u64, subcommand: &str, options: &[CommandDataOption]) -> Reply {
+    match subcommand {
+        "list" => {
+            let reminders = {
+                let data = ctx.data.read().await;
+                let db = data.get::<DataStore>().unwrap().lock().await;
+                let reminders = db.get::<HashMap<u64, Vec<Reminder>>>("reminders").unwrap_or_default();
+                reminders.get(&user_id).cloned().unwrap_or_default()
+            };
+
+            match reminders.is_empty() {
+                true => Reply::Text("You have no reminders".into()),
+                false => Reply::Text(reminders.iter()
+                    .map(reminder_line)
+                    .collect::<Vec<_>>()
+                    .join("\n")),
+            }
+        }
+        "add" => {
+            let name = string_option(options, "tracker").unwrap_or_default();
+            let leads = match parse_durations(string_option(options, "lead").unwrap_or_default()) {
+                Some(leads) if leads.is_empty() => Some(vec![Duration::minutes(5)]),
+                leads => leads,
+            };
+
+            match (Tracker::from_name(name), leads) {
+                (Some(tracker), Some(leads)) if tracker.next_start(Utc::now(), &TierConfig::default()).is_some() => {
+                    update_reminders(ctx, user_id, |reminders| {
+                        for lead in &leads {
+                            if !reminders.iter().any(|reminder| reminder.tracker == tracker && reminder.lead() == Some(*lead)) {
+                                reminders.push(Reminder::new(tracker, *lead));
+                            }
+                        }
+                    }).await;
+                    let leads: Vec<String> = leads.into_iter().map(format_duration).collect();
+                    Reply::Text(format!("I'll DM you {} before {}", leads.join(", "), tracker.to_name()))
+                }
+                (Some(tracker), Some(_)) => Reply::Text(format!("{} has no windows to be reminded about", tracker.to_name())),
+                (Some(_), None) => Reply::Text(format!("Could not read `{}` as lead times of up to {MAX_DURATION_HOURS}h", string_option(options, "lead").unwrap_or_default())),
+                (None, _) => Reply::Text(format!("Could not find tracker `{name}`")),
+            }
+        }
+        "cancel" => match string_option(options, "tracker") {
+            Some(name) => match Tracker::from_name(name) {
+                Some(tracker) => {
+                    let cancelled = update_reminders(ctx, user_id, |reminders| {
+                        let before = reminders.len();
+                        reminders.retain(|reminder| reminder.tracker != tracker);
+                        reminders.len() < before
+                    }).await;
+                    match cancelled {
+                        true => Reply::Text(format!("Cancelled your reminders for {}", tracker.to_name())),
+                        false => Reply::Text(format!("You have no reminders for {}", tracker.to_name())),
+                    }
+                }
+                None => Reply::Text(format!("Could not find tracker `{name}`")),
+            },
+            None => {
+                let cancelled = update_reminders(ctx, user_id, |reminders| {
+                    let cancelled = !reminders.is_empty();
+                    reminders.clear();
+                    cancelled
+                }).await;
+                match cancelled {
+                    true => Reply::Text("Cancelled all your reminders".into()),
+                    false => Reply::Text("You have no reminders".into()),
+                }
+            }
+        },
+        _ => Reply::Text("Unknown command".into()),
+    }
+}
+
+async fn run_autocomplete(ctx: &Context, autocomplete: &AutocompleteInteraction) -> serenity::Result<()> {
+    let focused = subcommand(&autocomplete.data.options).1.iter().find(|option| option.focused);
+    let (name, partial) = match focused {
+        Some(option) => (option.name.as_str(), option.value.as_ref().and_then(|value| value.as_str()).unwrap_or_default().to_lowercase()),
+        None => return Ok(()),
+    };
+
+    autocomplete.create_autocomplete_response(&ctx.http, |r| {
+        match name {
+            "tracker" => {
+                let trackers = Tracker::names().into_iter()
+                    .filter(|tracker| tracker.to_lowercase().contains(&partial))
+                    .take(MAX_CHOICES);
+                for tracker in trackers {
+                    r.add_string_choice(&tracker, &tracker);
+                }
+            }
+            "zone" => {
+                let zones = EorzeaMap::names().into_iter()
+                    .filter(|zone| zone.to_lowercase().contains(&partial))
+                    .take(MAX_CHOICES);
+                for zone in zones {
+                    r.add_string_choice(&zone, &zone);
+                }
+            }
+            "hunt" => {
+                let names = HUNTS.iter().map(|hunt| hunt.name.as_str()).chain(hunt_zones())
+                    .filter(|name| name.to_lowercase().contains(&partial))
+                    .take(MAX_CHOICES);
+                for name in names {
+                    r.add_string_choice(name, name);
+                }
+            }
+            "route" => {
+                let routes = Route::ALL.into_iter().map(|route| format!("{route:?}"))
+                    .filter(|route| route.to_lowercase().contains(&partial))
+                    .take(MAX_CHOICES);
+                for route in routes {
+                    r.add_string_choice(&route, &route);
+                }
+            }
+            "node" => {
+                let names = NODES.iter().map(|node| node.name.as_str())
+                    .filter(|name| name.to_lowercase().contains(&partial))
+                    .take(MAX_CHOICES);
+                for name in names {
+                    r.add_string_choice(name, name);
+                }
+            }
+            _ => {}
+        }
+        r
+    }).await
+}
+
+/// Reads a guild's configuration
+async fn guild_item(ctx: &Context, guild_id: u64) -> GuildItem {
+    let data = ctx.data.read().await;
+    let db = data.get::<DataStore>().unwrap().lock().await;
+    let guilds = db.get::<HashMap<u64, GuildItem>>("guilds").unwrap_or_default();
+    guilds.get(&guild_id).cloned().unwrap_or_default()
+}
+
+/// Changes a guild's configuration and saves it, passing back whatever the change returns
+async fn update_guild<R, F: FnOnce(&mut GuildItem) -> R>(ctx: &Context, guild_id: u64, f: F) -> R {
+    let mut data = ctx.data.write().await;
+    let mut db = data.get_mut::<DataStore>().unwrap().lock().await;
+    let mut guilds = db.get::<HashMap<u64, GuildItem>>("guilds").unwrap_or_default();
+
+    let result = f(guilds.entry(guild_id).or_default());
+
+    db.set("guilds", &guilds).unwrap();
+    db.dump().expect("failed to save db");
+    result
+}
+
+/// Changes a user's DM reminders and saves them, passing back whatever the change returns
+async fn update_reminders<R, F: FnOnce(&mut Vec<Reminder>) -> R>(ctx: &Context, user_id: u64, f: F) -> R {
+    let mut data = ctx.data.write().await;
+    let mut db = data.get_mut::<DataStore>().unwrap().lock().await;
+    let mut reminders = db.get::<HashMap<u64, Vec<Reminder>>>("reminders").unwrap_or_default();
+
+    let result = f(reminders.entry(user_id).or_default());
+
+    db.set("reminders", &reminders).unwrap();
+    db.dump().expect("failed to save db");
+    result
+}
+
+/// Changes a user's completed sightseeing log entries and saves them
+pub(super) async fn update_vistas<F: FnOnce(&mut Vec<usize>)>(ctx: &Context, user_id: u64, f: F) {
+    let mut data = ctx.data.write().await;
+    let mut db = data.get_mut::<DataStore>().unwrap().lock().await;
+    let mut vistas = db.get::<HashMap<u64, Vec<usize>>>("vistas").unwrap_or_default();
+
+    f(vistas.entry(user_id).or_default());
+
+    db.set("vistas", &vistas).unwrap();
+    db.dump().expect("failed to save db");
+}
+
+/// Changes a user's gathering node watchlist and saves it, passing back whatever the change returns
+pub(super) async fn update_nodes<R, F: FnOnce(&mut Vec<String>) -> R>(ctx: &Context, user_id: u64, f: F) -> R {
+    let mut data = ctx.data.write().await;
+    let mut db = data.get_mut::<DataStore>().unwrap().lock().await;
+    let mut nodes = db.get::<HashMap<u64, Vec<String>>>("nodes").unwrap_or_default();
+
+    let result = f(nodes.entry(user_id).or_default());
+
+    db.set("nodes", &nodes).unwrap();
+    db.dump().expect("failed to save db");
+    result
+}
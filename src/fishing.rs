@@ -0,0 +1,67 @@
+use crate::data::{Fish, FISH};
+use crate::datetime_eorzea::DateTimeEorzea;
+use crate::window::*;
+use chrono::Duration;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct CatchWindow {
+    pub fish: &'static Fish,
+    pub window: Window,
+}
+
+impl Fish {
+    pub fn from_name(name: &str) -> Option<&'static Fish> {
+        FISH.iter().find(|fish| fish.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Finds every catch window starting within the next `days` real days
+    pub fn windows(&self, now: DateTimeEorzea, days: i64) -> Vec<Window> {
+        let until = DateTimeEorzea::from_utc(now.to_utc() + Duration::days(days));
+        self.conditions.windows_until(now, until)
+    }
+}
+
+/// Finds catch windows for every fish within the next `days` real days, soonest first
+pub fn catch_windows(now: DateTimeEorzea, days: i64) -> Vec<CatchWindow> {
+    let mut windows: Vec<CatchWindow> = FISH.iter()
+        .flat_map(|fish| fish.windows(now, days).into_iter().map(move |window| CatchWindow { fish, window }))
+        .collect();
+
+    windows.sort_by_key(|fish| fish.window.start);
+    windows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::FishMap;
+    use chrono::Timelike;
+
+    fn start() -> DateTimeEorzea {
+        DateTimeEorzea::from_utc("2024-01-01T00:00:00Z".parse().unwrap())
+    }
+
+    #[test]
+    fn every_fish_can_be_searched() {
+        // Read the file directly, since FISH has already dropped any bad entries
+        let data = std::fs::read_to_string("data/fish.json").expect("Unable to read file");
+        let fish: FishMap = serde_json::from_str(&data).expect("Unable to parse map");
+
+        for fish in fish.iter() {
+            assert_eq!(fish.conditions.problem(), None, "{}", fish.name);
+        }
+        assert_eq!(FISH.len(), fish.len());
+    }
+
+    #[test]
+    fn catch_windows_meet_conditions() {
+        let windows = catch_windows(start(), 7);
+
+        assert!(!windows.is_empty());
+        assert!(windows.windows(2).all(|pair| pair[0].window.start <= pair[1].window.start));
+        for catch in &windows {
+            let bells = catch.fish.conditions.bells.unwrap();
+            assert!(bells.contains(catch.window.start.hour()), "{} at {:?}", catch.fish.name, catch.window);
+        }
+    }
+}
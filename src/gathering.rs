@@ -0,0 +1,98 @@
+use crate::data::{Node, NODES};
+use crate::datetime_eorzea::DateTimeEorzea;
+use crate::window::*;
+use chrono::{DateTime, Utc};
+
+/// When a node is up, in real time
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct NodeSpawn {
+    pub node: &'static Node,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl Node {
+    pub fn from_name(name: &str) -> Option<&'static Node> {
+        NODES.iter().find(|node| node.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Whether this node is on the watchlist, where an empty watchlist watches every node
+    pub fn is_watched(&self, watchlist: &[String]) -> bool {
+        watchlist.is_empty() || watchlist.iter().any(|name| self.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Finds up to `count` windows where this node is up
+    pub fn windows(&self, now: DateTimeEorzea, count: usize) -> Vec<Window> {
+        self.conditions.windows(now, count)
+    }
+}
+
+/// Finds upcoming spawns for nodes on the watchlist, or every node if it is empty, soonest first
+/// The first spawn of each node may already be up at `now`
+pub fn node_spawns(watchlist: &[String], now: DateTime<Utc>, count: usize) -> Vec<NodeSpawn> {
+    let now = DateTimeEorzea::from_utc(now);
+    let mut spawns: Vec<NodeSpawn> = NODES.iter()
+        .filter(|node| node.is_watched(watchlist))
+        .flat_map(|node| node.windows(now, count).into_iter().map(move |window| NodeSpawn {
+            node,
+            start: window.start.to_utc(),
+            end: window.end.to_utc(),
+        }))
+        .collect();
+
+    spawns.sort_by_key(|spawn| spawn.start);
+    spawns.truncate(count);
+    spawns
+}
+
+/// Finds the watched nodes which next appear together at or after `after`, ignoring any already up
+pub fn next_node_spawns(watchlist: &[String], after: DateTime<Utc>) -> Vec<NodeSpawn> {
+    // Each node has at most one spawn already up, so twice the nodes leaves at least one upcoming spawn each
+    let nodes = NODES.iter().filter(|node| node.is_watched(watchlist)).count();
+    let spawns: Vec<NodeSpawn> = node_spawns(watchlist, after, nodes * 2).into_iter()
+        .filter(|spawn| spawn.start >= after)
+        .collect();
+
+    match spawns.first() {
+        Some(first) => {
+            let start = first.start;
+            spawns.into_iter().filter(|spawn| spawn.start == start).collect()
+        }
+        None => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn start() -> DateTime<Utc> {
+        "2024-01-01T00:00:00Z".parse().unwrap()
+    }
+
+    #[test]
+    fn watchlist_limits_spawns() {
+        let name = NODES[0].name.to_uppercase();
+        let spawns = node_spawns(&[name], start(), 3);
+
+        assert_eq!(spawns.len(), 3);
+        assert!(spawns.iter().all(|spawn| spawn.node.name == NODES[0].name));
+    }
+
+    #[test]
+    fn empty_watchlist_lists_every_node() {
+        let spawns = node_spawns(&[], start(), 5);
+
+        assert_eq!(spawns.len(), 5);
+        assert!(spawns.windows(2).all(|pair| pair[0].start <= pair[1].start));
+    }
+
+    #[test]
+    fn next_spawns_are_soonest_upcoming() {
+        let after = start();
+        let next = next_node_spawns(&[], after);
+        assert!(!next.is_empty());
+        assert!(next.iter().all(|spawn| spawn.start == next[0].start && spawn.start >= after));
+        assert!(node_spawns(&[], after, NODES.len() * 2).iter().all(|spawn| spawn.start < after || spawn.start >= next[0].start));
+    }
+}
@@ -0,0 +1,131 @@
+use crate::data::{Hunt, HUNTS};
+use crate::datetime_eorzea::DateTimeEorzea;
+use crate::window::*;
+use chrono::Duration;
+
+/// Extra windows searched for on top of those asked for, to make up for any too short for a hunt's delay
+const WINDOW_MARGIN: usize = 4;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct HuntWindow {
+    pub hunt: &'static Hunt,
+    pub window: Window,
+}
+
+impl Hunt {
+    pub fn from_name(name: &str) -> Option<&'static Hunt> {
+        HUNTS.iter().find(|hunt| hunt.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Finds up to `count` windows where this hunt is able to spawn
+    pub fn windows(&self, now: DateTimeEorzea, count: usize) -> Vec<Window> {
+        let delay = Duration::minutes(self.delay);
+        // A run already in progress counts its delay from when it began, not from this cycle
+        let run_start = self.conditions.run_start(now);
+
+        // Windows too short for the delay are skipped, so search further until enough are left
+        let mut search = count + WINDOW_MARGIN;
+        loop {
+            let mut candidates = self.conditions.windows(now, search);
+            let exhausted = candidates.len() < search;
+
+            if let (Some(first), Some(start)) = (candidates.first_mut(), run_start) {
+                first.start = start;
+            }
+
+            let windows: Vec<Window> = candidates.into_iter()
+                .filter(|window| window.duration() > delay)
+                .map(|window| Window::new(DateTimeEorzea::from_utc(window.start.to_utc() + delay), window.end))
+                .filter(|window| window.end > now)
+                .take(count)
+                .collect();
+
+            if windows.len() == count || exhausted {
+                return windows;
+            }
+            search *= 2;
+        }
+    }
+}
+
+/// Finds spawn windows for every hunt in a zone, soonest first
+pub fn zone_hunt_windows(zone: &str, now: DateTimeEorzea, count: usize) -> Vec<HuntWindow> {
+    let mut windows: Vec<HuntWindow> = HUNTS.iter()
+        .filter(|hunt| hunt.conditions.zone == zone)
+        .flat_map(|hunt| hunt.windows(now, count).into_iter().map(move |window| HuntWindow { hunt, window }))
+        .collect();
+
+    windows.sort_by_key(|hunt| hunt.window.start);
+    windows.truncate(count);
+    windows
+}
+
+/// Finds the next spawn window for every hunt, soonest first
+pub fn all_hunt_windows(now: DateTimeEorzea, count: usize) -> Vec<HuntWindow> {
+    let mut windows: Vec<HuntWindow> = HUNTS.iter()
+        .filter_map(|hunt| hunt.windows(now, 1).first().map(|&window| HuntWindow { hunt, window }))
+        .collect();
+
+    windows.sort_by_key(|hunt| hunt.window.start);
+    windows.truncate(count);
+    windows
+}
+
+/// Every zone with a tracked hunt
+pub fn hunt_zones() -> Vec<&'static str> {
+    let mut zones: Vec<&'static str> = HUNTS.iter().map(|hunt| hunt.conditions.zone.as_str()).collect();
+    zones.sort();
+    zones.dedup();
+    zones
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn garlok() -> &'static Hunt {
+        Hunt::from_name("The Garlok").expect("Could not find hunt")
+    }
+
+    fn start() -> DateTimeEorzea {
+        DateTimeEorzea::from_utc("2024-01-01T00:00:00Z".parse().unwrap())
+    }
+
+    #[test]
+    fn query_mid_run_keeps_open_window() {
+        let hunt = garlok();
+        let window = hunt.windows(start(), 1)[0];
+
+        // The run began several cycles before its spawn window opened
+        let now = window.start + Duration::minutes(1);
+        assert_eq!(hunt.windows(now, 1), vec![window]);
+    }
+
+    #[test]
+    fn query_before_delay_is_met_finds_same_window() {
+        let hunt = garlok();
+        let window = hunt.windows(start(), 1)[0];
+
+        let now = DateTimeEorzea::from_utc(window.start.to_utc() - Duration::minutes(hunt.delay / 2));
+        assert_eq!(hunt.windows(now, 1), vec![window]);
+    }
+
+    #[test]
+    fn every_hunt_listed_once_soonest_first() {
+        let windows = all_hunt_windows(start(), HUNTS.len());
+
+        assert_eq!(windows.len(), HUNTS.len());
+        assert!(HUNTS.iter().all(|hunt| windows.iter().filter(|spawn| spawn.hunt == hunt).count() == 1));
+        assert!(windows.windows(2).all(|pair| pair[0].window.start <= pair[1].window.start));
+    }
+
+    #[test]
+    fn short_query_matches_longer_query() {
+        // Single rain cycles are too short for the delay, so the search has to go past them
+        let hunt = Hunt::from_name("Laideronnette").expect("Could not find hunt");
+        let windows = hunt.windows(start(), 8);
+
+        assert_eq!(windows.len(), 8);
+        assert_eq!(hunt.windows(start(), 2), windows[..2]);
+    }
+}
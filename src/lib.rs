@@ -1,20 +1,28 @@
 pub mod data;
 pub mod datetime_eorzea;
 pub mod discord;
+pub mod fishing;
+pub mod gathering;
+pub mod hunt;
 pub mod ocean;
+pub mod sightseeing;
 pub mod status;
 pub mod store;
 pub mod time;
+pub mod tracker;
 pub mod weather;
+pub mod window;
 
 pub mod prelude {
     pub use crate::{
-        data::{MAP_INFO, WEATHER_NAMES, WEATHER_RATES},
+        data::{FISH, HUNTS, MAP_INFO, NODES, VISTAS, WEATHER_NAMES, WEATHER_RATES},
         discord,
         status::*,
         store::*,
         datetime_eorzea::*,
         time::*,
+        tracker::*,
         weather::*,
+        window::*,
     };
-}
\ No newline at end of file
+}
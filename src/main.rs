@@ -5,7 +5,8 @@ use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 
 use eureka_notify::{prelude::*, discord::*};
-use chrono::{Duration, Utc};
+use eureka_notify::ocean::{Route, RouteTier, Voyage};
+use chrono::{DateTime, Duration, Utc};
 use chrono_humanize::HumanTime;
 use derive_more::{Deref, DerefMut};
 use lazy_static::lazy_static;
@@ -34,18 +35,25 @@ use tokio::time::sleep;
 use tracing::*;
 use tracing_subscriber;
 
+/// How often DM reminders are checked, in seconds
+const REMINDER_INTERVAL: u64 = 30;
+
 // Globals loaded from environment vars
 lazy_static! {
     pub static ref DISCORD_TOKEN: String = env::var("DISCORD_TOKEN").expect("Expected DISCORD_TOKEN in env");
     pub static ref DB: Arc<Mutex<PickleDb>> = Arc::new(Mutex::new(PickleDb::load("data.db", PickleDbDumpPolicy::DumpUponRequest, SerializationMethod::Json).unwrap_or_else(|_| PickleDb::new("data.db", PickleDbDumpPolicy::DumpUponRequest, SerializationMethod::Json))));
 }
 
-#[derive(serde::Serialize, serde::Deserialize, Debug, Default)]
-pub struct GuildItem {
-    pub channel_id: Option<u64>,
-    pub role_id: Option<u64>,
-    pub posts: Vec<(u64, i64)>,
-    pub notifications: Vec<u64>,
+/// Scheduled work between two weather ticks
+enum Event {
+    /// Alerts ahead of windows starting in the given cycle, for guilds using this lead time
+    Notify(DateTimeEorzea, Duration),
+    /// Alerts that a tracker's window ends at the given time, for guilds using this lead time
+    Ending(Tracker, Duration, DateTimeEorzea),
+    /// A voyage begins boarding, so the ocean fishing board moves on
+    OceanBoard(DateTime<Utc>),
+    /// A voyage begins boarding soon
+    OceanPing(Voyage),
 }
 
 #[tokio::main]
@@ -90,10 +98,9 @@ async fn main() -> anyhow::Result<()> {
 #[group]
 // This requires us to call commands in this group
 #[prefixes("ross", "br")]
-#[only_in(guilds)]
 #[summary = "Ross commands"]
 // Sets a command that will be executed if only a group-prefix was passed.
-#[commands(notify, ping)]
+#[commands(notify, ping, ocean, channel, track, ending, forecast, next, hunt, vista, node, at, et, remind)]
 pub struct Ross;
 
 #[derive(Default, Deref, DerefMut)]
@@ -106,9 +113,15 @@ impl EventHandler for DiscordHandler {
         if !self.load(Ordering::Relaxed) {
             self.swap(true, Ordering::Relaxed);
 
+            register_commands(&ctx).await;
+            tokio::spawn(run_reminder_loop(ctx.clone()));
             tokio::spawn(run_main_loop(ctx));
         }
     }
+
+    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        handle_interaction(&ctx, interaction).await;
+    }
 }
 
 async fn run_main_loop(ctx: Context) {
@@ -129,17 +142,6 @@ async fn run_main_loop(ctx: Context) {
     loop {
         let future = now + Duration::hours(8);
 
-        let crab = crab_status(now, Direction::Future);
-        let crab = if crab == future { Some(crab) } else { None };
-
-        let cassie = cassie_status(now, Direction::Future);
-        let cassie = if cassie == future { Some(cassie) } else { None };
-
-        let skoll = skoll_status(now, Direction::Future);
-        let skoll = if skoll == future { Some(skoll) } else { None };
-
-        let do_notify = crab.is_some() || cassie.is_some() || skoll.is_some();
-
         if skip_first_tick {
             info!("skipping same tick");
             skip_first_tick = false;
@@ -147,24 +149,73 @@ async fn run_main_loop(ctx: Context) {
             let mut db = DB.lock().await;
             let mut guilds: HashMap<u64, GuildItem> = db.get("guilds").unwrap_or(HashMap::new());
 
+            let no_roles = TrackerRoles::default();
+
             for (_, guild) in guilds.iter_mut() {
-                if let Some(channel_id) = guild.channel_id {
-                    // Post updates
-                    let post_id = post_discord(&ctx, channel_id, guild.role_id, now).await;
+                for binding in guild.channels.iter_mut() {
+                    let channel_id = binding.channel_id;
+
+                    // Post updates, only pinging in channels that also take alerts
+                    let post_id = match binding.has(ChannelContent::Board) {
+                        true if binding.has(ChannelContent::Alerts) => post_discord(&ctx, channel_id, guild.role_id, &guild.roles, &guild.trackers, now).await,
+                        true => post_discord(&ctx, channel_id, None, &no_roles, &guild.trackers, now).await,
+                        false => None,
+                    };
+
+                    // Keep the live status message current, pinging separately in channels that also take alerts
+                    let live = binding.has(ChannelContent::Status);
+                    if live {
+                        binding.status_id = update_status(&ctx, channel_id, binding.status_id, &guild.trackers, now).await;
+                    } else if let Some(id) = binding.status_id.take() {
+                        delete_post(&ctx, channel_id, id).await;
+                    }
+
+                    // Pings are only needed until their windows begin, so they're deleted on the next tick
+                    for id in binding.pings.drain(..) {
+                        delete_post(&ctx, channel_id, id).await;
+                    }
+                    if live && binding.has(ChannelContent::Alerts) {
+                        if let Some(id) = ping_upcoming(&ctx, channel_id, guild.role_id, &guild.roles, &guild.trackers, now).await {
+                            binding.pings.push(id);
+                        }
+                    }
 
                     // Clean up historical posts
-                    for (id, timestamp) in guild.posts.drain(..) {
-                        edit_post(&ctx, channel_id, id, DateTimeEorzea::from_timestamp(timestamp)).await;
+                    for (id, timestamp) in binding.posts.drain(..) {
+                        edit_post(&ctx, channel_id, id, &guild.trackers, DateTimeEorzea::from_timestamp(timestamp)).await;
                     }
 
                     // Clean up historical notifications
-                    for id in guild.notifications.drain(..) {
-                        edit_notification(&ctx, channel_id, id, now).await;
+                    for id in binding.notifications.drain(..) {
+                        edit_notification(&ctx, channel_id, id, &guild.trackers, now).await;
+                    }
+
+                    // Clean up alerts for windows which have begun
+                    // Live channels delete them instead of editing, since the status message already shows the window
+                    let (started, pending): (Vec<_>, Vec<_>) = binding.alerts.drain(..).partition(|(_, start)| *start <= now.and_utc().timestamp());
+                    binding.alerts = pending;
+                    for (id, start) in started {
+                        if live {
+                            delete_post(&ctx, channel_id, id).await;
+                            continue;
+                        }
+                        edit_notification(&ctx, channel_id, id, &guild.trackers, DateTimeEorzea::from_timestamp(start)).await;
+                    }
+
+                    // Follow up on windows which have ended, deleting the alert in live channels as above
+                    let (ended, pending): (Vec<_>, Vec<_>) = binding.endings.drain(..).partition(|(_, _, end)| *end <= now.and_utc().timestamp());
+                    binding.endings = pending;
+                    for (id, tracker, end) in ended {
+                        if live {
+                            delete_post(&ctx, channel_id, id).await;
+                            continue;
+                        }
+                        edit_ending(&ctx, channel_id, id, tracker, DateTimeEorzea::from_timestamp(end)).await;
                     }
 
                     // Push this post to history
                     if let Some(id) = post_id {
-                        guild.posts.push((id, now.timestamp()));
+                        binding.posts.push((id, now.timestamp()));
                     }
                 }
             }
@@ -176,22 +227,140 @@ async fn run_main_loop(ctx: Context) {
             info!("Completed tick for {}", now.to_utc());
         }
 
-        // Wait until 5 minutes before the next cycle to send a notification
-        if do_notify {
-            if let Ok(duration) = ((future.to_utc() - Duration::minutes(5)) - Utc::now()).to_std() {
-                info!("sleep for {:?} to 5-minute notification", duration);
-                sleep(duration).await;
+        // Gather everything that happens before the next cycle
+        let mut events = vec![];
+
+        // Lead times longer than a cycle alert for windows a few cycles ahead
+        let guilds: HashMap<u64, GuildItem> = DB.lock().await.get("guilds").unwrap_or_default();
+        let alerting = guilds.values().filter(|guild| guild.channels_with(ChannelContent::Alerts).next().is_some());
+        let mut leads: Vec<Duration> = alerting.clone().flat_map(|guild| guild.lead_times()).collect();
+        leads.sort();
+        leads.dedup();
+
+        for lead in leads {
+            // Leads are capped, so only the few cycles one could alert for are checked
+            for cycle in 0..=lead.num_hours() / 8 + 1 {
+                let start = future + Duration::hours(8 * cycle);
+                let time = start.to_utc() - lead;
+                if time >= now.to_utc() && time < future.to_utc() && !Tracker::nms_starting(start).is_empty() {
+                    events.push((time, Event::Notify(start, lead)));
+                }
+            }
+        }
+
+        let mut endings: Vec<(Tracker, i64)> = alerting.flat_map(|guild| guild.endings.iter().copied()).collect();
+        endings.sort_by_key(|(tracker, lead)| (tracker.to_name(), *lead));
+        endings.dedup();
 
-                let mut db = DB.lock().await;
-                let mut guilds: HashMap<u64, GuildItem> = db.get("guilds").unwrap_or(HashMap::new());
+        for (tracker, lead) in endings {
+            let Some(lead) = stored_lead(lead) else { continue };
+            for current in tracker.windows(now, 4) {
+                let Some(time) = current.window.end.to_utc().checked_sub_signed(lead) else { continue };
+                if time >= now.to_utc() && time < future.to_utc() && time >= current.window.start.to_utc() {
+                    events.push((time, Event::Ending(tracker, lead, current.window.end)));
+                }
+            }
+        }
+
+        let ping = Duration::minutes(OCEAN_PING_MINUTES);
+        for voyage in Route::schedule(now.to_utc(), future.to_utc() + ping) {
+            if voyage.departure < future.to_utc() {
+                events.push((voyage.departure, Event::OceanBoard(voyage.departure)));
+            }
+            if voyage.departure - ping >= now.to_utc() && voyage.departure - ping < future.to_utc() {
+                events.push((voyage.departure - ping, Event::OceanPing(voyage)));
+            }
+        }
+        events.sort_by_key(|(time, _)| *time);
+
+        for (time, event) in events {
+            // Skip anything we've already missed
+            let duration = match (time - Utc::now()).to_std() {
+                Ok(duration) => duration,
+                Err(_) => continue,
+            };
+
+            match event {
+                Event::Notify(start, lead) => {
+                    info!("sleep for {:?} to {:?} notification", duration, lead);
+                    sleep(duration).await;
+
+                    let mut db = DB.lock().await;
+                    let mut guilds: HashMap<u64, GuildItem> = db.get("guilds").unwrap_or(HashMap::new());
+
+                    for (_, guild) in guilds.iter_mut().filter(|(_, guild)| guild.lead_times().contains(&lead)) {
+                        for binding in guild.channels.iter_mut().filter(|binding| binding.has(ChannelContent::Alerts)) {
+                            // Push this post to history
+                            if let Some(id) = notify_discord(&ctx, binding.channel_id, guild.role_id, &guild.roles, &guild.trackers, start - Duration::hours(8)).await {
+                                binding.alerts.push((id, start.and_utc().timestamp()));
+                            }
+                        }
+                    }
+
+                    db.set("guilds", &guilds).unwrap();
+                    db.dump().expect("failed to save db");
+                }
+                Event::Ending(tracker, lead, end) => {
+                    info!("sleep for {:?} to {} ending notification", duration, tracker.to_name());
+                    sleep(duration).await;
+
+                    let mut db = DB.lock().await;
+                    let mut guilds: HashMap<u64, GuildItem> = db.get("guilds").unwrap_or(HashMap::new());
+
+                    for (_, guild) in guilds.iter_mut().filter(|(_, guild)| guild.ending_lead(tracker) == Some(lead)) {
+                        for binding in guild.channels.iter_mut().filter(|binding| binding.has(ChannelContent::Alerts)) {
+                            if let Some(id) = notify_ending(&ctx, binding.channel_id, tracker, end).await {
+                                binding.endings.push((id, tracker, end.and_utc().timestamp()));
+                            }
+                        }
+                    }
+
+                    db.set("guilds", &guilds).unwrap();
+                    db.dump().expect("failed to save db");
+                }
+                // Replace the ocean fishing board once a voyage starts boarding
+                Event::OceanBoard(departure) => {
+                    info!("sleep for {:?} to ocean board", duration);
+                    sleep(duration).await;
+
+                    let mut db = DB.lock().await;
+                    let mut guilds: HashMap<u64, GuildItem> = db.get("guilds").unwrap_or(HashMap::new());
+
+                    for (_, guild) in guilds.iter_mut() {
+                        for binding in guild.channels.iter_mut() {
+                            // Clean up the previous board and its pings
+                            for id in binding.ocean_posts.drain(..) {
+                                delete_post(&ctx, binding.channel_id, id).await;
+                            }
+
+                            if binding.has(ChannelContent::Ocean) {
+                                if let Some(id) = post_ocean(&ctx, binding.channel_id, &guild.trackers, &guild.tiers, departure).await {
+                                    binding.ocean_posts.push(id);
+                                }
+                            }
+                        }
+                    }
 
-                for (_, guild) in guilds.iter_mut() {
-                    if let Some(channel_id) = guild.channel_id {
-                        // Push this post to history
-                        if let Some(id) = notify_discord(&ctx, channel_id, guild.role_id, now).await {
-                            guild.notifications.push(id);
+                    db.set("guilds", &guilds).unwrap();
+                    db.dump().expect("failed to save db");
+                }
+                Event::OceanPing(voyage) => {
+                    info!("sleep for {:?} to ocean notification", duration);
+                    sleep(duration).await;
+
+                    let mut db = DB.lock().await;
+                    let mut guilds: HashMap<u64, GuildItem> = db.get("guilds").unwrap_or(HashMap::new());
+
+                    for (_, guild) in guilds.iter_mut().filter(|(_, guild)| guild.trackers.pings_route(voyage.route, &guild.tiers)) {
+                        for binding in guild.channels.iter_mut().filter(|binding| binding.has(ChannelContent::OceanAlerts)) {
+                            if let Some(id) = notify_ocean(&ctx, binding.channel_id, guild.ocean_role_id, &guild.roles, &guild.tiers, &voyage).await {
+                                binding.ocean_posts.push(id);
+                            }
                         }
                     }
+
+                    db.set("guilds", &guilds).unwrap();
+                    db.dump().expect("failed to save db");
                 }
             }
         }
@@ -206,6 +375,46 @@ async fn run_main_loop(ctx: Context) {
     }
 }
 
+async fn run_reminder_loop(ctx: Context) {
+    info!("Starting reminder loop");
+
+    loop {
+        let now = Utc::now();
+
+        // Find what is due without holding the lock while DMs are sent
+        let due: Vec<(u64, Reminder, DateTime<Utc>)> = {
+            let db = DB.lock().await;
+            let reminders: HashMap<u64, Vec<Reminder>> = db.get("reminders").unwrap_or_default();
+            reminders.into_iter()
+                .flat_map(|(user_id, list)| list.into_iter().filter_map(move |reminder| {
+                    let start = reminder.due(now)?;
+                    Some((user_id, reminder, start))
+                }))
+                .collect()
+        };
+
+        if !due.is_empty() {
+            for (user_id, reminder, start) in &due {
+                send_reminder(&ctx, *user_id, reminder.tracker, *start).await;
+            }
+
+            // Only try each window once, even if the DM can't be delivered
+            let mut db = DB.lock().await;
+            let mut reminders: HashMap<u64, Vec<Reminder>> = db.get("reminders").unwrap_or_default();
+            for (user_id, sent, start) in due {
+                let list = reminders.get_mut(&user_id).into_iter().flatten();
+                for reminder in list.filter(|reminder| reminder.tracker == sent.tracker && reminder.lead == sent.lead) {
+                    reminder.last_sent = Some(start.timestamp());
+                }
+            }
+            db.set("reminders", &reminders).unwrap();
+            db.dump().expect("failed to save db");
+        }
+
+        sleep(std::time::Duration::from_secs(REMINDER_INTERVAL)).await;
+    }
+}
+
 #[hook]
 async fn before(ctx: &Context, msg: &Message, command_name: &str) -> bool {
     msg.react(ctx, '✅').await.ok();
@@ -216,8 +425,9 @@ async fn before(ctx: &Context, msg: &Message, command_name: &str) -> bool {
 #[command]
 #[description = "Explains the current notification configuration"]
 #[bucket = "ross"]
-#[sub_commands(notify_set, notify_clear)]
+#[sub_commands(notify_set, notify_clear, notify_lead)]
 #[required_permissions("ADMINISTRATOR")]
+#[only_in(guilds)]
 pub async fn notify(ctx: &Context, msg: &Message) -> CommandResult {
     let mut data = ctx.data.write().await;
     let db = data.get_mut::<DataStore>().unwrap().lock().await;
@@ -226,14 +436,19 @@ pub async fn notify(ctx: &Context, msg: &Message) -> CommandResult {
 
     let mut success = false;
     if let Some(guild) = guild {
-        if let Some(channel_id) = guild.channel_id {
+        for binding in guild.channels_with(ChannelContent::Board) {
             if let Ok(channels) = msg.guild_id.unwrap().channels(&ctx).await {
-                if let Some(channel) = channels.get(&ChannelId(channel_id)) {
+                if let Some(channel) = channels.get(&ChannelId(binding.channel_id)) {
                     msg.reply(&ctx.http, format!("Posting in {channel}")).await?;
                     success = true;
                 }
             }
         }
+
+        if success {
+            let leads: Vec<String> = guild.lead_times().into_iter().map(format_duration).collect();
+            msg.reply(&ctx.http, format!("Alerting {} before each window", leads.join(", "))).await?;
+        }
     }
 
     if !success {
@@ -251,10 +466,13 @@ pub async fn notify_set(ctx: &Context, msg: &Message, _args: Args) -> CommandRes
     let mut guilds = db.get::<HashMap<u64, GuildItem>>("guilds").unwrap_or(HashMap::new());
     let mut guild = guilds.entry(msg.guild_id.unwrap().0).or_insert(Default::default());
 
-    guild.channel_id = Some(msg.channel_id.0);
+    let stale = guild.rebind(msg.channel_id.0, &[ChannelContent::Board, ChannelContent::Alerts]);
 
     db.set("guilds", &guilds).unwrap();
     db.dump().expect("failed to save db");
+    drop(db);
+    drop(data);
+    delete_posts(ctx, &stale).await;
 
     msg.reply(&ctx.http, format!("Will now post updates in this channel")).await?;
 
@@ -270,21 +488,53 @@ pub async fn notify_clear(ctx: &Context, msg: &Message, _args: Args) -> CommandR
     let mut guilds = db.get::<HashMap<u64, GuildItem>>("guilds").unwrap_or(HashMap::new());
     let mut guild = guilds.entry(msg.guild_id.unwrap().0).or_insert(Default::default());
 
-    guild.channel_id = None;
+    let stale = guild.unbind(None, &[ChannelContent::Board, ChannelContent::Alerts]);
 
     db.set("guilds", &guilds).unwrap();
     db.dump().expect("failed to save db");
+    drop(db);
+    drop(data);
+    delete_posts(ctx, &stale).await;
 
     msg.reply(&ctx.http, format!("No longer sending updates")).await?;
 
     Ok(())
 }
 
+#[command("lead")]
+#[description("Sets how long before each Crab, Cassie or Skoll window to send alerts, such as `30m 10m 2m`, or the default 5 minutes if none are given. Ocean voyages are pinged separately in OceanAlerts channels")]
+#[usage = "[lead times]"]
+pub async fn notify_lead(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let leads = match parse_durations(args.rest()) {
+        Some(leads) => leads,
+        None => {
+            msg.reply(&ctx.http, format!("Could not read `{}` as lead times of up to {MAX_DURATION_HOURS}h", args.rest())).await?;
+            return Ok(());
+        }
+    };
+
+    let mut data = ctx.data.write().await;
+    let mut db = data.get_mut::<DataStore>().unwrap().lock().await;
+    let mut guilds = db.get::<HashMap<u64, GuildItem>>("guilds").unwrap_or(HashMap::new());
+    let guild = guilds.entry(msg.guild_id.unwrap().0).or_insert(Default::default());
+
+    guild.set_lead_times(&leads);
+    let leads: Vec<String> = guild.lead_times().into_iter().map(format_duration).collect();
+
+    db.set("guilds", &guilds).unwrap();
+    db.dump().expect("failed to save db");
+
+    msg.reply(&ctx.http, format!("Will send alerts {} before each window", leads.join(", "))).await?;
+
+    Ok(())
+}
+
 #[command]
 #[description = "Explains the current mention configuration"]
 #[bucket = "ross"]
-#[sub_commands(ping_set, ping_clear)]
+#[sub_commands(ping_set, ping_clear, ping_for, ping_unset)]
 #[required_permissions("ADMINISTRATOR")]
+#[only_in(guilds)]
 pub async fn ping(ctx: &Context, msg: &Message) -> CommandResult {
     let mut data = ctx.data.write().await;
     let db = data.get_mut::<DataStore>().unwrap().lock().await;
@@ -307,6 +557,77 @@ pub async fn ping(ctx: &Context, msg: &Message) -> CommandResult {
         msg.reply(&ctx.http, format!("Not currently set to ping.\nUse `^ross ping set <id>` to set a role")).await?;
     }
 
+    if let Some(guild) = guild.filter(|guild| !guild.roles.is_empty()) {
+        let lines: Vec<String> = guild.roles.iter()
+            .map(|(tracker, role_id)| format!("{}: {}", tracker.to_name(), RoleId(*role_id).mention()))
+            .collect();
+        msg.channel_id.send_message(&ctx.http, |m| m.reference_message(msg).content(lines.join("\n")).allowed_mentions(|a| a.empty_roles())).await?;
+    }
+
+    Ok(())
+}
+
+#[command("for")]
+#[description("Sets the role to ping for a single tracker instead of the server-wide role")]
+#[usage = "<tracker> <id>"]
+pub async fn ping_for(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let parts: Vec<&str> = args.rest().split(|c: char| c.is_whitespace() || c == ',').filter(|part| !part.is_empty()).collect();
+    let (tracker, role_id) = match parts[..] {
+        [tracker, role_id] => (Tracker::from_name(tracker), role_id.parse::<u64>().ok()),
+        _ => (None, None),
+    };
+    let (tracker, role_id) = match (tracker, role_id) {
+        (Some(tracker), Some(role_id)) => (tracker, role_id),
+        _ => {
+            msg.reply(&ctx.http, "Use `^ross ping for <tracker> <id>`, e.g. `^ross ping for Crab 1234`").await?;
+            return Ok(());
+        }
+    };
+
+    let mut data = ctx.data.write().await;
+    let mut db = data.get_mut::<DataStore>().unwrap().lock().await;
+    let mut guilds = db.get::<HashMap<u64, GuildItem>>("guilds").unwrap_or(HashMap::new());
+    let guild = guilds.entry(msg.guild_id.unwrap().0).or_insert(Default::default());
+
+    guild.roles.set(tracker, role_id);
+
+    db.set("guilds", &guilds).unwrap();
+    db.dump().expect("failed to save db");
+
+    msg.channel_id.send_message(&ctx.http, |m| {
+        m.reference_message(msg)
+            .content(format!("Will now ping {} for {}", RoleId(role_id).mention(), tracker.to_name()))
+            .allowed_mentions(|a| a.empty_roles())
+    }).await?;
+
+    Ok(())
+}
+
+#[command("unset")]
+#[description("Pings the server-wide role for a tracker again")]
+#[usage = "<tracker>"]
+pub async fn ping_unset(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let tracker = match Tracker::from_name(args.rest().trim()) {
+        Some(tracker) => tracker,
+        None => {
+            msg.reply(&ctx.http, format!("Could not find tracker `{}`", args.rest())).await?;
+            return Ok(());
+        }
+    };
+
+    let mut data = ctx.data.write().await;
+    let mut db = data.get_mut::<DataStore>().unwrap().lock().await;
+    let mut guilds = db.get::<HashMap<u64, GuildItem>>("guilds").unwrap_or(HashMap::new());
+
+    if let Some(guild) = guilds.get_mut(&msg.guild_id.unwrap().0) {
+        guild.roles.unset(tracker);
+    }
+
+    db.set("guilds", &guilds).unwrap();
+    db.dump().expect("failed to save db");
+
+    msg.reply(&ctx.http, format!("No longer using a separate role for {}", tracker.to_name())).await?;
+
     Ok(())
 }
 
@@ -374,4 +695,489 @@ pub async fn ping_clear(ctx: &Context, msg: &Message, _args: Args) -> CommandRes
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[command]
+#[description = "Explains the current ocean fishing configuration"]
+#[bucket = "ross"]
+#[sub_commands(ocean_set, ocean_clear, ocean_ping, ocean_ping_clear, ocean_tier, ocean_override)]
+#[required_permissions("ADMINISTRATOR")]
+#[only_in(guilds)]
+pub async fn ocean(ctx: &Context, msg: &Message) -> CommandResult {
+    let mut data = ctx.data.write().await;
+    let db = data.get_mut::<DataStore>().unwrap().lock().await;
+    let guilds = db.get::<HashMap<u64, GuildItem>>("guilds").unwrap_or(HashMap::new());
+    let guild = guilds.get(&msg.guild_id.unwrap().0);
+
+    let mut lines = vec![];
+    if let Some(guild) = guild {
+        for binding in guild.channels_with(ChannelContent::Ocean) {
+            if let Ok(channels) = msg.guild_id.unwrap().channels(&ctx).await {
+                if let Some(channel) = channels.get(&ChannelId(binding.channel_id)) {
+                    lines.push(format!("Posting ocean fishing in {channel}"));
+                }
+            }
+        }
+        if let Some(role_id) = guild.ocean_role_id {
+            if let Ok(roles) = msg.guild_id.unwrap().roles(&ctx).await {
+                if let Some(role) = roles.get(&RoleId(role_id)) {
+                    lines.push(format!("Will ping `@{}` before notable voyages", role.name));
+                }
+            }
+        }
+        if !lines.is_empty() {
+            lines.extend(tier_lines(&guild.tiers));
+        }
+    }
+
+    if lines.is_empty() {
+        msg.reply(&ctx.http, "Not currently set to post ocean fishing.\nUse `^ross ocean set` to set the current channel").await?;
+    } else {
+        msg.reply(&ctx.http, lines.join("\n")).await?;
+    }
+
+    Ok(())
+}
+
+#[command("set")]
+#[description("Sets the channel to post ocean fishing voyages")]
+pub async fn ocean_set(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
+    let mut data = ctx.data.write().await;
+    let mut db = data.get_mut::<DataStore>().unwrap().lock().await;
+    let mut guilds = db.get::<HashMap<u64, GuildItem>>("guilds").unwrap_or(HashMap::new());
+    let guild = guilds.entry(msg.guild_id.unwrap().0).or_insert(Default::default());
+
+    let stale = guild.rebind(msg.channel_id.0, &[ChannelContent::Ocean, ChannelContent::OceanAlerts]);
+
+    db.set("guilds", &guilds).unwrap();
+    db.dump().expect("failed to save db");
+    drop(db);
+    drop(data);
+    delete_posts(ctx, &stale).await;
+
+    msg.reply(&ctx.http, "Will now post ocean fishing voyages in this channel").await?;
+
+    Ok(())
+}
+
+#[command("stop")]
+#[aliases("clear", "remove")]
+#[description("Stops the bot from posting ocean fishing voyages")]
+pub async fn ocean_clear(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
+    let mut data = ctx.data.write().await;
+    let mut db = data.get_mut::<DataStore>().unwrap().lock().await;
+    let mut guilds = db.get::<HashMap<u64, GuildItem>>("guilds").unwrap_or(HashMap::new());
+    let guild = guilds.entry(msg.guild_id.unwrap().0).or_insert(Default::default());
+
+    let stale = guild.unbind(None, &[ChannelContent::Ocean, ChannelContent::OceanAlerts]);
+
+    db.set("guilds", &guilds).unwrap();
+    db.dump().expect("failed to save db");
+    drop(db);
+    drop(data);
+    delete_posts(ctx, &stale).await;
+
+    msg.reply(&ctx.http, "No longer posting ocean fishing voyages").await?;
+
+    Ok(())
+}
+
+#[command("ping")]
+#[description("Sets the role to ping before notable voyages")]
+#[usage = "<id>"]
+pub async fn ocean_ping(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let role_id = args.single::<u64>()?;
+
+    let mut data = ctx.data.write().await;
+    let mut db = data.get_mut::<DataStore>().unwrap().lock().await;
+    let mut guilds = db.get::<HashMap<u64, GuildItem>>("guilds").unwrap_or(HashMap::new());
+    let guild = guilds.entry(msg.guild_id.unwrap().0).or_insert(Default::default());
+
+    let mut role = None;
+    if let Ok(roles) = msg.guild_id.unwrap().roles(&ctx).await {
+        if let Some(r) = roles.get(&RoleId(role_id)) {
+            role = Some(r.clone());
+        }
+    }
+
+    guild.ocean_role_id = Some(role_id);
+
+    db.set("guilds", &guilds).unwrap();
+    db.dump().expect("failed to save db");
+
+    match role {
+        Some(role) => msg.reply(&ctx.http, format!("Will now ping {} before notable voyages", role)).await?,
+        None => msg.reply(&ctx.http, format!("Will try to ping <@{}> before notable voyages", role_id)).await?,
+    };
+
+    Ok(())
+}
+
+#[command("unping")]
+#[aliases("noping")]
+#[description("Stops the bot from pinging before notable voyages")]
+pub async fn ocean_ping_clear(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
+    let mut data = ctx.data.write().await;
+    let mut db = data.get_mut::<DataStore>().unwrap().lock().await;
+    let mut guilds = db.get::<HashMap<u64, GuildItem>>("guilds").unwrap_or(HashMap::new());
+
+    if let Some(guild) = guilds.get_mut(&msg.guild_id.unwrap().0) {
+        guild.ocean_role_id = None;
+    }
+
+    db.set("guilds", &guilds).unwrap();
+    db.dump().expect("failed to save db");
+
+    msg.reply(&ctx.http, "No longer pinging before notable voyages").await?;
+
+    Ok(())
+}
+
+#[command("tier")]
+#[aliases("threshold")]
+#[description("Sets the lowest route tier worth pinging for: Bad, Good, Very Good or Best")]
+#[usage = "<tier>"]
+pub async fn ocean_tier(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let tier = match RouteTier::from_name(args.rest().trim()) {
+        Some(tier) => tier,
+        None => {
+            msg.reply(&ctx.http, format!("Could not find tier `{}`, try Bad, Good, Very Good or Best", args.rest().trim())).await?;
+            return Ok(());
+        }
+    };
+
+    let mut data = ctx.data.write().await;
+    let mut db = data.get_mut::<DataStore>().unwrap().lock().await;
+    let mut guilds = db.get::<HashMap<u64, GuildItem>>("guilds").unwrap_or(HashMap::new());
+    let guild = guilds.entry(msg.guild_id.unwrap().0).or_insert(Default::default());
+
+    guild.tiers.threshold = tier;
+
+    db.set("guilds", &guilds).unwrap();
+    db.dump().expect("failed to save db");
+
+    msg.reply(&ctx.http, format!("Will ping for {} routes and better", tier.to_name())).await?;
+
+    Ok(())
+}
+
+#[command("override")]
+#[description("Changes the tier of a single route, or puts it back to its default when given no tier")]
+#[usage = "<route> [tier]"]
+pub async fn ocean_override(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let name = args.single::<String>()?;
+    let route = match Route::from_name(&name) {
+        Some(route) => route,
+        None => {
+            msg.reply(&ctx.http, format!("Could not find route `{name}`")).await?;
+            return Ok(());
+        }
+    };
+    let tier = match args.rest().trim() {
+        "" => None,
+        text => match RouteTier::from_name(text) {
+            Some(tier) => Some(tier),
+            None => {
+                msg.reply(&ctx.http, format!("Could not find tier `{text}`, try Bad, Good, Very Good or Best")).await?;
+                return Ok(());
+            }
+        },
+    };
+
+    let mut data = ctx.data.write().await;
+    let mut db = data.get_mut::<DataStore>().unwrap().lock().await;
+    let mut guilds = db.get::<HashMap<u64, GuildItem>>("guilds").unwrap_or(HashMap::new());
+    let guild = guilds.entry(msg.guild_id.unwrap().0).or_insert(Default::default());
+
+    match tier {
+        Some(tier) => guild.tiers.overrides.insert(route, tier),
+        None => guild.tiers.overrides.remove(&route),
+    };
+
+    db.set("guilds", &guilds).unwrap();
+    db.dump().expect("failed to save db");
+
+    match tier {
+        Some(tier) => msg.reply(&ctx.http, format!("Now treating {route:?} as {}", tier.to_name())).await?,
+        None => msg.reply(&ctx.http, format!("Using the default tier for {route:?}")).await?,
+    };
+
+    Ok(())
+}
+
+#[command]
+#[description = "Lists the trackers this server follows"]
+#[bucket = "ross"]
+#[sub_commands(track_add, track_remove)]
+#[required_permissions("ADMINISTRATOR")]
+#[only_in(guilds)]
+pub async fn track(ctx: &Context, msg: &Message) -> CommandResult {
+    let mut data = ctx.data.write().await;
+    let db = data.get_mut::<DataStore>().unwrap().lock().await;
+    let guilds = db.get::<HashMap<u64, GuildItem>>("guilds").unwrap_or(HashMap::new());
+    let trackers = guilds.get(&msg.guild_id.unwrap().0).map(|guild| guild.trackers.clone()).unwrap_or_default();
+
+    let following = match trackers.is_empty() {
+        true => "Following everything".to_string(),
+        false => format!("Following {}", trackers.iter().map(|tracker| tracker.to_name()).collect::<Vec<_>>().join(", ")),
+    };
+    let available = Tracker::ALL.iter().map(|tracker| tracker.to_name()).collect::<Vec<_>>().join(", ");
+
+    msg.reply(&ctx.http, format!("{following}\nAvailable: {available}, or any ocean route\nUse `^ross track add <name>` to follow a tracker")).await?;
+
+    Ok(())
+}
+
+#[command("add")]
+#[description("Follows a tracker, such as Crab, Pagos or an ocean route")]
+#[usage = "<tracker>"]
+pub async fn track_add(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let tracker = match Tracker::from_name(args.rest().trim()) {
+        Some(tracker) => tracker,
+        None => {
+            msg.reply(&ctx.http, format!("Could not find tracker `{}`", args.rest())).await?;
+            return Ok(());
+        }
+    };
+
+    let mut data = ctx.data.write().await;
+    let mut db = data.get_mut::<DataStore>().unwrap().lock().await;
+    let mut guilds = db.get::<HashMap<u64, GuildItem>>("guilds").unwrap_or(HashMap::new());
+    let guild = guilds.entry(msg.guild_id.unwrap().0).or_insert(Default::default());
+
+    if !guild.trackers.follow(tracker) {
+        msg.reply(&ctx.http, format!("Already following {}", tracker.to_name())).await?;
+        return Ok(());
+    }
+
+    db.set("guilds", &guilds).unwrap();
+    db.dump().expect("failed to save db");
+
+    msg.reply(&ctx.http, format!("Now following {}", tracker.to_name())).await?;
+
+    Ok(())
+}
+
+#[command("remove")]
+#[aliases("stop")]
+#[description("Stops following a tracker")]
+#[usage = "<tracker>"]
+pub async fn track_remove(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let tracker = match Tracker::from_name(args.rest().trim()) {
+        Some(tracker) => tracker,
+        None => {
+            msg.reply(&ctx.http, format!("Could not find tracker `{}`", args.rest())).await?;
+            return Ok(());
+        }
+    };
+
+    let mut data = ctx.data.write().await;
+    let mut db = data.get_mut::<DataStore>().unwrap().lock().await;
+    let mut guilds = db.get::<HashMap<u64, GuildItem>>("guilds").unwrap_or(HashMap::new());
+
+    let guild = guilds.entry(msg.guild_id.unwrap().0).or_default();
+    if !guild.trackers.unfollow(tracker) {
+        msg.reply(&ctx.http, format!("{} is the only tracker followed, add another before removing it", tracker.to_name())).await?;
+        return Ok(());
+    }
+
+    db.set("guilds", &guilds).unwrap();
+    db.dump().expect("failed to save db");
+
+    msg.reply(&ctx.http, format!("No longer following {}", tracker.to_name())).await?;
+
+    Ok(())
+}
+
+#[command]
+#[description = "Lists the trackers which get alerts before their windows end"]
+#[bucket = "ross"]
+#[sub_commands(ending_add, ending_remove)]
+#[required_permissions("ADMINISTRATOR")]
+#[only_in(guilds)]
+pub async fn ending(ctx: &Context, msg: &Message) -> CommandResult {
+    let mut data = ctx.data.write().await;
+    let db = data.get_mut::<DataStore>().unwrap().lock().await;
+    let guilds = db.get::<HashMap<u64, GuildItem>>("guilds").unwrap_or(HashMap::new());
+    let endings = guilds.get(&msg.guild_id.unwrap().0).map(|guild| guild.endings.clone()).unwrap_or_default();
+
+    let lines: Vec<String> = endings.iter()
+        .map(|(tracker, lead)| ending_line(*tracker, *lead))
+        .collect();
+
+    match lines.is_empty() {
+        true => msg.reply(&ctx.http, "No window-ending alerts.\nUse `^ross ending add <tracker> [lead]` to add one").await?,
+        false => msg.reply(&ctx.http, lines.join("\n")).await?,
+    };
+
+    Ok(())
+}
+
+#[command("add")]
+#[description("Alerts before a tracker's windows end, 10 minutes before unless given a lead time such as 5m")]
+#[usage = "<tracker> [lead]"]
+pub async fn ending_add(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let (name, lead) = args.rest().trim().split_once(char::is_whitespace).unwrap_or((args.rest().trim(), ""));
+    let lead = match lead.trim() {
+        "" => Some(Duration::minutes(DEFAULT_ENDING_MINUTES)),
+        lead => parse_duration(lead).filter(|lead| *lead > Duration::zero()),
+    };
+    let lead = match lead {
+        Some(lead) => lead,
+        None => {
+            msg.reply(&ctx.http, format!("Could not read `{}` as a lead time of up to {MAX_DURATION_HOURS}h", args.rest())).await?;
+            return Ok(());
+        }
+    };
+
+    let tracker = match Tracker::from_name(name) {
+        Some(tracker) if !tracker.windows(DateTimeEorzea::now(), 1).is_empty() => tracker,
+        Some(tracker) => {
+            msg.reply(&ctx.http, format!("{} has no windows to end", tracker.to_name())).await?;
+            return Ok(());
+        }
+        None => {
+            msg.reply(&ctx.http, format!("Could not find tracker `{name}`")).await?;
+            return Ok(());
+        }
+    };
+
+    let mut data = ctx.data.write().await;
+    let mut db = data.get_mut::<DataStore>().unwrap().lock().await;
+    let mut guilds = db.get::<HashMap<u64, GuildItem>>("guilds").unwrap_or(HashMap::new());
+    let guild = guilds.entry(msg.guild_id.unwrap().0).or_insert(Default::default());
+
+    guild.set_ending(tracker, Some(lead));
+
+    db.set("guilds", &guilds).unwrap();
+    db.dump().expect("failed to save db");
+
+    msg.reply(&ctx.http, format!("Will alert {} before {} windows end", format_duration(lead), tracker.to_name())).await?;
+
+    Ok(())
+}
+
+#[command("remove")]
+#[aliases("stop")]
+#[description("Stops alerting before a tracker's windows end")]
+#[usage = "<tracker>"]
+pub async fn ending_remove(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let tracker = match Tracker::from_name(args.rest().trim()) {
+        Some(tracker) => tracker,
+        None => {
+            msg.reply(&ctx.http, format!("Could not find tracker `{}`", args.rest())).await?;
+            return Ok(());
+        }
+    };
+
+    let mut data = ctx.data.write().await;
+    let mut db = data.get_mut::<DataStore>().unwrap().lock().await;
+    let mut guilds = db.get::<HashMap<u64, GuildItem>>("guilds").unwrap_or(HashMap::new());
+
+    if let Some(guild) = guilds.get_mut(&msg.guild_id.unwrap().0) {
+        guild.set_ending(tracker, None);
+    }
+
+    db.set("guilds", &guilds).unwrap();
+    db.dump().expect("failed to save db");
+
+    msg.reply(&ctx.http, format!("No longer alerting before {} windows end", tracker.to_name())).await?;
+
+    Ok(())
+}
+
+/// Reads content types such as "Board, Alerts", returning None if any are unknown
+fn parse_content(list: &str) -> Option<Vec<ChannelContent>> {
+    list.split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|name| !name.is_empty())
+        .map(ChannelContent::from_name)
+        .collect()
+}
+
+#[command]
+#[description = "Lists every channel the bot posts in and what it posts there"]
+#[bucket = "ross"]
+#[sub_commands(channel_add, channel_remove)]
+#[required_permissions("ADMINISTRATOR")]
+#[only_in(guilds)]
+pub async fn channel(ctx: &Context, msg: &Message) -> CommandResult {
+    let mut data = ctx.data.write().await;
+    let db = data.get_mut::<DataStore>().unwrap().lock().await;
+    let guilds = db.get::<HashMap<u64, GuildItem>>("guilds").unwrap_or(HashMap::new());
+    let channels = guilds.get(&msg.guild_id.unwrap().0).map(|guild| guild.channels.clone()).unwrap_or_default();
+
+    let lines: Vec<String> = channels.iter()
+        .map(|binding| format!("{}: {}", ChannelId(binding.channel_id).mention(), binding.content.iter().map(|content| content.to_name()).collect::<Vec<_>>().join(", ")))
+        .collect();
+    let available = ChannelContent::ALL.iter().map(|content| content.to_name()).collect::<Vec<_>>().join(", ");
+
+    match lines.is_empty() {
+        true => msg.reply(&ctx.http, format!("Not currently posting anywhere.\nUse `^ross channel add <content>` to post in this channel, choosing from {available}")).await?,
+        false => msg.reply(&ctx.http, format!("{}\nAvailable: {available}", lines.join("\n"))).await?,
+    };
+
+    Ok(())
+}
+
+#[command("add")]
+#[description("Posts the given content in this channel, e.g. `Board, Alerts`")]
+#[usage = "<content...>"]
+pub async fn channel_add(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let content = match parse_content(args.rest()) {
+        Some(content) if !content.is_empty() => content,
+        _ => {
+            let available = ChannelContent::ALL.iter().map(|content| content.to_name()).collect::<Vec<_>>().join(", ");
+            msg.reply(&ctx.http, format!("Choose content from {available}")).await?;
+            return Ok(());
+        }
+    };
+
+    let mut data = ctx.data.write().await;
+    let mut db = data.get_mut::<DataStore>().unwrap().lock().await;
+    let mut guilds = db.get::<HashMap<u64, GuildItem>>("guilds").unwrap_or(HashMap::new());
+    let guild = guilds.entry(msg.guild_id.unwrap().0).or_insert(Default::default());
+
+    guild.bind(msg.channel_id.0, &content);
+
+    db.set("guilds", &guilds).unwrap();
+    db.dump().expect("failed to save db");
+
+    msg.reply(&ctx.http, format!("Will now post {} in this channel", content.iter().map(|content| content.to_name()).collect::<Vec<_>>().join(", "))).await?;
+
+    Ok(())
+}
+
+#[command("remove")]
+#[aliases("stop", "clear")]
+#[description("Stops posting the given content in this channel, or everything if none is given")]
+#[usage = "[content...]"]
+pub async fn channel_remove(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let content = match parse_content(args.rest()) {
+        Some(content) if content.is_empty() => ChannelContent::ALL.to_vec(),
+        Some(content) => content,
+        None => {
+            let available = ChannelContent::ALL.iter().map(|content| content.to_name()).collect::<Vec<_>>().join(", ");
+            msg.reply(&ctx.http, format!("Choose content from {available}")).await?;
+            return Ok(());
+        }
+    };
+
+    let mut data = ctx.data.write().await;
+    let mut db = data.get_mut::<DataStore>().unwrap().lock().await;
+    let mut guilds = db.get::<HashMap<u64, GuildItem>>("guilds").unwrap_or(HashMap::new());
+
+    let stale = match guilds.get_mut(&msg.guild_id.unwrap().0) {
+        Some(guild) => guild.unbind(Some(msg.channel_id.0), &content),
+        None => vec![],
+    };
+
+    db.set("guilds", &guilds).unwrap();
+    db.dump().expect("failed to save db");
+    drop(db);
+    drop(data);
+    delete_posts(ctx, &stale).await;
+
+    msg.reply(&ctx.http, format!("No longer posting {} in this channel", content.iter().map(|content| content.to_name()).collect::<Vec<_>>().join(", "))).await?;
+
+    Ok(())
+}
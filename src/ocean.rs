@@ -1,8 +1,10 @@
-use crate::time::*;
+use std::collections::HashMap;
+use crate::data::{RouteTargets, OCEAN_TARGETS, OCEAN_TIERS};
+use crate::status::Direction;
 use chrono::{DateTime, Duration, DurationRound, Utc};
 use tracing::info;
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+#[derive(serde::Serialize, serde::Deserialize, Copy, Clone, Eq, PartialEq, Debug, Hash)]
 pub enum Route {
     BloodbrineDay,
     RothlytDay,
@@ -19,45 +21,312 @@ pub enum Route {
 }
 
 impl Route {
+    pub const ALL: [Route; 12] = [
+        Route::BloodbrineDay, Route::RothlytDay, Route::MerlthorDay, Route::RhotanoDay,
+        Route::BloodbrineSunset, Route::RothlytSunset, Route::MerlthorSunset, Route::RhotanoSunset,
+        Route::BloodbrineNight, Route::RothlytNight, Route::MerlthorNight, Route::RhotanoNight,
+    ];
+
+    /// Finds a route from its identifier, e.g. "MerlthorNight"
+    pub fn from_name(name: &str) -> Option<Self> {
+        Route::ALL.into_iter().find(|route| format!("{route:?}").eq_ignore_ascii_case(name))
+    }
+
     pub fn from_datetime(dt: DateTime<Utc>) -> Self {
         let duration = Duration::hours(2);
 
         // Calculate index based off magic offset
-        let floored = dt.clone().truncated(duration);
-        let voyage = OFFSET + (floored.timestamp() / duration.num_seconds()) as usize;
+        // Euclidean division keeps departures before the epoch on the same cycle
+        let voyage = OFFSET as i64 + dt.timestamp().div_euclid(duration.num_seconds());
 
         // Find in table
-        PATTERN[voyage % 144]
+        PATTERN[voyage.rem_euclid(PATTERN.len() as i64) as usize]
     }
 
-    pub fn to_name(self) -> &'static str {
+    /// Lists every departure between two times, in order from `from` to `to`
+    /// Works backwards in time when `to` is before `from`
+    pub fn schedule(from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<Voyage> {
+        let direction = if to >= from { Direction::Future } else { Direction::Past };
+        let (start, end) = if to >= from { (from, to) } else { (to, from) };
+
+        voyages(from, direction)
+            .take_while(|voyage| start <= voyage.departure && voyage.departure <= end)
+            .collect()
+    }
+
+    /// Finds the next `n` departures of this route, nearest first
+    pub fn next_occurrences(self, from: DateTime<Utc>, n: usize, direction: Direction) -> Vec<Voyage> {
+        voyages(from, direction)
+            .filter(|voyage| voyage.route == self)
+            .take(n)
+            .collect()
+    }
+
+    /// Fishing areas visited, in order
+    pub fn areas(self) -> [FishingArea; 3] {
+        match self {
+            Route::MerlthorDay | Route::MerlthorSunset | Route::MerlthorNight => [FishingArea::GaladionBay, FishingArea::SouthernMerlthor, FishingArea::NorthernMerlthor],
+            Route::RhotanoDay | Route::RhotanoSunset | Route::RhotanoNight => [FishingArea::GaladionBay, FishingArea::SouthernMerlthor, FishingArea::Rhotano],
+            Route::BloodbrineDay | Route::BloodbrineSunset | Route::BloodbrineNight => [FishingArea::CieldalaesMargin, FishingArea::NorthernMerlthor, FishingArea::Bloodbrine],
+            Route::RothlytDay | Route::RothlytSunset | Route::RothlytNight => [FishingArea::CieldalaesMargin, FishingArea::Rhotano, FishingArea::Rothlyt],
+        }
+    }
+
+    /// Time of day at the final stop
+    pub fn period(self) -> TimePeriod {
         match self {
-            Route::BloodbrineDay => "Crab / Seafaring Toad",
-            Route::RothlytDay => "Fugu / Mantas",
-            Route::MerlthorDay => "Sothis / Elasmosaurus",
-            Route::RhotanoDay => "Shark / Coral Manta",
-            Route::BloodbrineSunset => "Hafgufa / Elasmosaurus",
-            Route::RothlytSunset => "Hafgufa / Placodus",
-            Route::MerlthorSunset => "Seadragons / Coral Manta",
-            Route::RhotanoSunset => "Sothis / Stonescale",
-            Route::BloodbrineNight => "Mantas",
-            Route::RothlytNight => "Fugu / Stonescale",
-            Route::MerlthorNight => "Octopodes",
-            Route::RhotanoNight => "Jellyfish",
+            Route::BloodbrineDay | Route::RothlytDay | Route::MerlthorDay | Route::RhotanoDay => TimePeriod::Day,
+            Route::BloodbrineSunset | Route::RothlytSunset | Route::MerlthorSunset | Route::RhotanoSunset => TimePeriod::Sunset,
+            Route::BloodbrineNight | Route::RothlytNight | Route::MerlthorNight | Route::RhotanoNight => TimePeriod::Night,
+        }
+    }
+
+    /// Lists the route's targets, e.g. "Crab / Seafaring Toad", falling back to its destination
+    pub fn to_name(self) -> String {
+        match self.targets() {
+            Some(targets) => targets.targets.iter().map(|target| target.name.as_str()).collect::<Vec<_>>().join(" / "),
+            None => format!("{} ({})", self.areas()[2].to_name(), self.period().to_name()),
         }
     }
+
+    /// Finds this route in the target catalogue
+    pub fn targets(self) -> Option<&'static RouteTargets> {
+        OCEAN_TARGETS.iter().find(|targets| targets.route == self)
+    }
+
+    /// Checks if any of this route's targets match the given name
+    pub fn has_target(self, name: &str) -> bool {
+        self.targets().is_some_and(|targets| targets.targets.iter().any(|target| target.name.eq_ignore_ascii_case(name)))
+    }
+}
+
+/// Walks voyages one departure at a time, starting with the nearest in the given direction
+pub fn voyages(from: DateTime<Utc>, direction: Direction) -> impl Iterator<Item = Voyage> {
+    let step = match direction {
+        Direction::Future => Duration::hours(2),
+        Direction::Past => -Duration::hours(2),
+    };
+
+    let mut departure = Voyage::from_datetime(from).departure;
+    if direction == Direction::Future && departure < from {
+        departure += step;
+    }
+
+    std::iter::successors(Some(departure), move |&departure| Some(departure + step))
+        .map(Voyage::from_datetime)
 }
 
+/// Finds every route offering the given target
+pub fn routes_with_target(name: &str) -> Vec<Route> {
+    OCEAN_TARGETS.iter()
+        .map(|targets| targets.route)
+        .filter(|route| route.has_target(name))
+        .collect()
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
 pub enum TimePeriod {
     Day,
     Sunset,
     Night,
 }
 
-pub const BEST_ROUTES: [Route; 4] = [Route::BloodbrineDay, Route::RothlytDay, Route::RhotanoDay, Route::RhotanoNight];
-pub const VERY_GOOD_ROUTES: [Route; 4] = [Route::RothlytSunset, Route::MerlthorSunset, Route::RothlytNight, Route::MerlthorNight];
-pub const GOOD_ROUTES: [Route; 1] = [Route::BloodbrineNight];
-pub const BAD_ROUTES: [Route; 3] = [Route::MerlthorDay, Route::BloodbrineSunset, Route::RhotanoSunset];
+impl TimePeriod {
+    /// The period following this one at the next stop
+    pub fn next(self) -> Self {
+        match self {
+            TimePeriod::Day => TimePeriod::Sunset,
+            TimePeriod::Sunset => TimePeriod::Night,
+            TimePeriod::Night => TimePeriod::Day,
+        }
+    }
+
+    pub fn to_name(self) -> &'static str {
+        match self {
+            TimePeriod::Day => "Day",
+            TimePeriod::Sunset => "Sunset",
+            TimePeriod::Night => "Night",
+        }
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum FishingArea {
+    GaladionBay,
+    SouthernMerlthor,
+    NorthernMerlthor,
+    Rhotano,
+    CieldalaesMargin,
+    Bloodbrine,
+    Rothlyt,
+}
+
+impl FishingArea {
+    pub fn to_name(self) -> &'static str {
+        match self {
+            FishingArea::GaladionBay => "Galadion Bay",
+            FishingArea::SouthernMerlthor => "The Southern Strait of Merlthor",
+            FishingArea::NorthernMerlthor => "The Northern Strait of Merlthor",
+            FishingArea::Rhotano => "Open Sea of Rhotano",
+            FishingArea::CieldalaesMargin => "Cieldalaes Margin",
+            FishingArea::Bloodbrine => "Open Sea of Bloodbrine",
+            FishingArea::Rothlyt => "Outer Rothlyt Sound",
+        }
+    }
+}
+
+/// Boarding closes this many minutes after departure, when the first stop begins
+pub const BOARDING_MINUTES: i64 = 15;
+
+/// Minutes between the start of each stop, including fishing time and travel
+pub const STOP_MINUTES: i64 = 10;
+
+/// Minutes into a stop by which the first spectral current has usually begun
+pub const SPECTRAL_MINUTES: i64 = 3;
+
+/// How long a single stop lasts, and when its spectral current is expected
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct StopTiming {
+    pub minutes: i64,
+    pub spectral_minutes: i64,
+}
+
+impl Default for StopTiming {
+    fn default() -> Self {
+        StopTiming {
+            minutes: STOP_MINUTES,
+            spectral_minutes: SPECTRAL_MINUTES,
+        }
+    }
+}
+
+/// Timings used to lay out the stops of a voyage
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct VoyageTimings {
+    pub boarding_minutes: i64,
+    pub stops: [StopTiming; 3],
+}
+
+impl Default for VoyageTimings {
+    fn default() -> Self {
+        VoyageTimings {
+            boarding_minutes: BOARDING_MINUTES,
+            stops: [StopTiming::default(); 3],
+        }
+    }
+}
+
+/// One leg of a voyage
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Stop {
+    pub area: FishingArea,
+    pub period: TimePeriod,
+    pub start: DateTime<Utc>,
+    /// When a spectral current is likely to have started
+    pub spectral: DateTime<Utc>,
+}
+
+/// A single departure of a route
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Voyage {
+    pub route: Route,
+    pub departure: DateTime<Utc>,
+}
+
+impl Voyage {
+    /// Finds the voyage which most recently began boarding
+    pub fn from_datetime(dt: DateTime<Utc>) -> Self {
+        let departure = dt.duration_trunc(Duration::hours(2)).expect("Departure out of range");
+        Voyage {
+            route: Route::from_datetime(departure),
+            departure,
+        }
+    }
+
+    /// Breaks this voyage into its stops, in order, using the standard timings
+    pub fn stops(&self) -> [Stop; 3] {
+        self.stops_with(&VoyageTimings::default())
+    }
+
+    /// Breaks this voyage into its stops, in order
+    pub fn stops_with(&self, timings: &VoyageTimings) -> [Stop; 3] {
+        let areas = self.route.areas();
+
+        // Each stop moves one period forward, ending on the route's period
+        let mut period = self.route.period().next();
+        let mut start = self.departure + Duration::minutes(timings.boarding_minutes);
+
+        std::array::from_fn(|index| {
+            let timing = timings.stops[index];
+            let stop = Stop {
+                area: areas[index],
+                period,
+                start,
+                spectral: start + Duration::minutes(timing.spectral_minutes),
+            };
+            period = period.next();
+            start += Duration::minutes(timing.minutes);
+            stop
+        })
+    }
+}
+
+/// How worthwhile a route is, from least to most
+#[derive(serde::Serialize, serde::Deserialize, Copy, Clone, Eq, PartialEq, Debug, Hash, Ord, PartialOrd)]
+pub enum RouteTier {
+    Bad,
+    Good,
+    VeryGood,
+    Best,
+}
+
+impl RouteTier {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().replace([' ', '_', '-'], "").as_str() {
+            "bad" => Some(RouteTier::Bad),
+            "good" => Some(RouteTier::Good),
+            "verygood" => Some(RouteTier::VeryGood),
+            "best" => Some(RouteTier::Best),
+            _ => None,
+        }
+    }
+
+    pub fn to_name(self) -> &'static str {
+        match self {
+            RouteTier::Bad => "Bad",
+            RouteTier::Good => "Good",
+            RouteTier::VeryGood => "Very Good",
+            RouteTier::Best => "Best",
+        }
+    }
+}
+
+/// Which routes are worth notifying for, on top of the default tiers
+#[derive(serde::Serialize, serde::Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct TierConfig {
+    /// Lowest tier worth notifying for
+    pub threshold: RouteTier,
+    pub overrides: HashMap<Route, RouteTier>,
+}
+
+impl Default for TierConfig {
+    fn default() -> Self {
+        TierConfig {
+            threshold: RouteTier::Good,
+            overrides: HashMap::new(),
+        }
+    }
+}
+
+impl TierConfig {
+    pub fn tier(&self, route: Route) -> Option<RouteTier> {
+        self.overrides.get(&route).or_else(|| OCEAN_TIERS.get(&route)).copied()
+    }
+
+    pub fn is_notable(&self, route: Route) -> bool {
+        self.tier(route).is_some_and(|tier| tier >= self.threshold)
+    }
+}
 
 
 const OFFSET: usize = 88;
@@ -0,0 +1,56 @@
+use crate::data::{Vista, VISTAS};
+use crate::datetime_eorzea::DateTimeEorzea;
+use crate::window::*;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct VistaWindow {
+    pub vista: &'static Vista,
+    pub window: Window,
+}
+
+impl Vista {
+    pub fn from_number(number: usize) -> Option<&'static Vista> {
+        VISTAS.iter().find(|vista| vista.number == number)
+    }
+
+    /// Finds up to `count` windows where this vista can be logged
+    pub fn windows(&self, now: DateTimeEorzea, count: usize) -> Vec<Window> {
+        self.conditions.windows(now, count)
+    }
+}
+
+/// Finds the next window for every entry not yet completed, soonest first
+pub fn vista_windows(completed: &[usize], now: DateTimeEorzea, count: usize) -> Vec<VistaWindow> {
+    let mut windows: Vec<VistaWindow> = VISTAS.iter()
+        .filter(|vista| !completed.contains(&vista.number))
+        .filter_map(|vista| vista.windows(now, 1).first().map(|&window| VistaWindow { vista, window }))
+        .collect();
+
+    windows.sort_by_key(|vista| vista.window.start);
+    windows.truncate(count);
+    windows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn start() -> DateTimeEorzea {
+        DateTimeEorzea::from_utc("2024-01-01T00:00:00Z".parse().unwrap())
+    }
+
+    #[test]
+    fn completed_entries_are_skipped() {
+        let windows = vista_windows(&[1, 2], start(), VISTAS.len());
+
+        assert_eq!(windows.len(), VISTAS.len() - 2);
+        assert!(windows.iter().all(|window| window.vista.number != 1 && window.vista.number != 2));
+    }
+
+    #[test]
+    fn windows_are_soonest_first() {
+        let windows = vista_windows(&[], start(), VISTAS.len());
+
+        assert!(windows.windows(2).all(|pair| pair[0].window.start <= pair[1].window.start));
+    }
+}
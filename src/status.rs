@@ -71,7 +71,7 @@ pub fn skoll_status(mut now: DateTimeEorzea, direction: Direction) -> DateTimeEo
     now
 }
 
-fn is_hotbox_weather(weather: EorzeaWeather) -> bool {
+pub fn is_hotbox_weather(weather: EorzeaWeather) -> bool {
     weather.name == "Snow" || weather.name == "Blizzards" || weather.name == "Umbral Wind"
 }
 
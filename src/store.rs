@@ -1,6 +1,10 @@
 use std::sync::Arc;
 use derive_more::{Deref, DerefMut};
 use pickledb::PickleDb;
+use crate::ocean::TierConfig;
+use crate::time::MAX_DURATION_HOURS;
+use crate::tracker::{Tracker, TrackerRoles, Trackers};
+use chrono::{DateTime, Duration, Utc};
 use serenity::prelude::TypeMapKey;
 use tokio::sync::Mutex;
 
@@ -10,3 +14,395 @@ pub struct DataStore(pub Arc<Mutex<PickleDb>>);
 impl TypeMapKey for DataStore {
     type Value = DataStore;
 }
+
+/// Kinds of message the bot can post in a channel
+#[derive(serde::Serialize, serde::Deserialize, Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum ChannelContent {
+    /// The Eureka weather board, posted every weather cycle
+    Board,
+    /// Pings ahead of upcoming Eureka windows
+    Alerts,
+    /// The ocean fishing board, posted as each voyage boards
+    Ocean,
+    /// Pings ahead of notable ocean fishing voyages
+    OceanAlerts,
+    /// A single pinned weather board, edited every weather cycle instead of posting a new one
+    /// Pings, alerts and ending alerts here are deleted once out of date rather than edited, so only the board stays
+    Status,
+}
+
+impl ChannelContent {
+    pub const ALL: [ChannelContent; 5] = [
+        ChannelContent::Board, ChannelContent::Alerts, ChannelContent::Ocean,
+        ChannelContent::OceanAlerts, ChannelContent::Status,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        ChannelContent::ALL.into_iter().find(|content| content.to_name().eq_ignore_ascii_case(name))
+    }
+
+    pub fn to_name(self) -> &'static str {
+        match self {
+            ChannelContent::Board => "Board",
+            ChannelContent::Alerts => "Alerts",
+            ChannelContent::Ocean => "Ocean",
+            ChannelContent::OceanAlerts => "OceanAlerts",
+            ChannelContent::Status => "Status",
+        }
+    }
+}
+
+/// A channel the bot posts in, along with the messages it still has to clean up there
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+pub struct ChannelBinding {
+    pub channel_id: u64,
+    pub content: Vec<ChannelContent>,
+    /// Weather boards and the cycle they were posted for, edited once out of date
+    #[serde(default)]
+    pub posts: Vec<(u64, i64)>,
+    /// Alerts and the cycle their windows begin, edited once it arrives
+    #[serde(default)]
+    pub alerts: Vec<(u64, i64)>,
+    /// Alerts from before their cycle was recorded, edited on the next tick
+    #[serde(default)]
+    pub notifications: Vec<u64>,
+    /// Window-ending alerts, their tracker and when the window ends, edited once it has
+    #[serde(default)]
+    pub endings: Vec<(u64, Tracker, i64)>,
+    /// The live status message, if one has been posted
+    #[serde(default)]
+    pub status_id: Option<u64>,
+    /// Pings sent alongside the live status message, deleted the next cycle
+    #[serde(default)]
+    pub pings: Vec<u64>,
+    /// Ocean fishing boards and alerts, deleted as the next voyage boards
+    #[serde(default)]
+    pub ocean_posts: Vec<u64>,
+}
+
+impl ChannelBinding {
+    pub fn has(&self, content: ChannelContent) -> bool {
+        self.content.contains(&content)
+    }
+
+    /// Every message the bot still has to clean up in this channel
+    pub fn messages(&self) -> Vec<u64> {
+        self.posts.iter().map(|(id, _)| *id)
+            .chain(self.alerts.iter().map(|(id, _)| *id))
+            .chain(self.notifications.iter().copied())
+            .chain(self.endings.iter().map(|(id, _, _)| *id))
+            .chain(self.status_id)
+            .chain(self.pings.iter().copied())
+            .chain(self.ocean_posts.iter().copied())
+            .collect()
+    }
+}
+
+/// How long before a window guilds are alerted unless they choose their own lead times
+pub const DEFAULT_LEAD_MINUTES: i64 = 5;
+
+/// Reads a lead saved in seconds, or None if it is longer than `MAX_DURATION_HOURS`
+/// Leads are capped when entered, so only those saved before the cap can be out of range
+pub fn stored_lead(seconds: i64) -> Option<Duration> {
+    Duration::try_seconds(seconds).filter(|lead| *lead <= Duration::hours(MAX_DURATION_HOURS))
+}
+
+/// How long before a window ends guilds are alerted unless they choose otherwise
+pub const DEFAULT_ENDING_MINUTES: i64 = 10;
+
+/// Per-guild configuration, stored under the "guilds" key
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+#[serde(from = "StoredGuildItem")]
+pub struct GuildItem {
+    pub channels: Vec<ChannelBinding>,
+    pub role_id: Option<u64>,
+    pub ocean_role_id: Option<u64>,
+    pub trackers: Trackers,
+    /// Roles pinged for individual trackers instead of `role_id` or `ocean_role_id`
+    pub roles: TrackerRoles,
+    /// Seconds before each window to send alerts, empty for `DEFAULT_LEAD_MINUTES`
+    pub lead_times: Vec<i64>,
+    /// Trackers with alerts before their windows end, and how many seconds before
+    pub endings: Vec<(Tracker, i64)>,
+    /// Which ocean fishing voyages are notable enough to ping for
+    pub tiers: TierConfig,
+}
+
+impl GuildItem {
+    /// How long before each window to send alerts, longest first, skipping any out of range
+    pub fn lead_times(&self) -> Vec<Duration> {
+        let leads: Vec<Duration> = self.lead_times.iter().filter_map(|seconds| stored_lead(*seconds)).collect();
+        match leads.is_empty() {
+            true => vec![Duration::minutes(DEFAULT_LEAD_MINUTES)],
+            false => leads,
+        }
+    }
+
+    /// Replaces the alert lead times, going back to the default if none are given
+    pub fn set_lead_times(&mut self, leads: &[Duration]) {
+        self.lead_times = leads.iter().map(|lead| lead.num_seconds()).collect();
+        self.lead_times.sort_unstable_by(|a, b| b.cmp(a));
+        self.lead_times.dedup();
+    }
+
+    /// How long before a tracker's windows end to send alerts, if at all
+    /// None as well for leads saved before they were capped at `MAX_DURATION_HOURS`
+    pub fn ending_lead(&self, tracker: Tracker) -> Option<Duration> {
+        self.endings.iter().find(|(ending, _)| *ending == tracker).and_then(|(_, seconds)| stored_lead(*seconds))
+    }
+
+    /// Turns window-ending alerts for a tracker on with the given lead time, or off with None
+    pub fn set_ending(&mut self, tracker: Tracker, lead: Option<Duration>) {
+        self.endings.retain(|(ending, _)| *ending != tracker);
+        if let Some(lead) = lead {
+            self.endings.push((tracker, lead.num_seconds()));
+        }
+    }
+
+    /// Channels which should receive the given content
+    pub fn channels_with(&self, content: ChannelContent) -> impl Iterator<Item = &ChannelBinding> {
+        self.channels.iter().filter(move |binding| binding.has(content))
+    }
+
+    /// Adds content to a channel, creating its binding if needed
+    pub fn bind(&mut self, channel_id: u64, content: &[ChannelContent]) {
+        let index = match self.channels.iter().position(|binding| binding.channel_id == channel_id) {
+            Some(index) => index,
+            None => {
+                self.channels.push(ChannelBinding { channel_id, ..Default::default() });
+                self.channels.len() - 1
+            }
+        };
+
+        let binding = &mut self.channels[index];
+        for content in content {
+            if !binding.has(*content) {
+                binding.content.push(*content);
+            }
+        }
+    }
+
+    /// Removes content from a channel, or from every channel if `channel_id` is None
+    /// Bindings left without any content are dropped, returning the channel and ID of each message they still tracked
+    pub fn unbind(&mut self, channel_id: Option<u64>, content: &[ChannelContent]) -> Vec<(u64, u64)> {
+        self.unbind_where(|binding| channel_id.is_none_or(|id| binding.channel_id == id), content)
+    }
+
+    /// Moves content to a single channel, removing it from every other
+    /// Returns the messages left behind in channels which no longer receive anything
+    pub fn rebind(&mut self, channel_id: u64, content: &[ChannelContent]) -> Vec<(u64, u64)> {
+        let stale = self.unbind_where(|binding| binding.channel_id != channel_id, content);
+        self.bind(channel_id, content);
+        stale
+    }
+
+    fn unbind_where<F: Fn(&ChannelBinding) -> bool>(&mut self, predicate: F, content: &[ChannelContent]) -> Vec<(u64, u64)> {
+        for binding in self.channels.iter_mut().filter(|binding| predicate(binding)) {
+            binding.content.retain(|bound| !content.contains(bound));
+        }
+
+        let (dropped, kept): (Vec<_>, Vec<_>) = self.channels.drain(..).partition(|binding| binding.content.is_empty());
+        self.channels = kept;
+        dropped.iter()
+            .flat_map(|binding| binding.messages().into_iter().map(|id| (binding.channel_id, id)))
+            .collect()
+    }
+}
+
+/// Every version of the guild configuration, so older databases keep working
+#[derive(serde::Deserialize)]
+struct StoredGuildItem {
+    #[serde(default)]
+    channels: Vec<ChannelBinding>,
+    role_id: Option<u64>,
+    #[serde(default)]
+    ocean_role_id: Option<u64>,
+    #[serde(default)]
+    trackers: Trackers,
+    #[serde(default)]
+    roles: TrackerRoles,
+    #[serde(default)]
+    lead_times: Vec<i64>,
+    #[serde(default)]
+    endings: Vec<(Tracker, i64)>,
+    #[serde(default)]
+    tiers: TierConfig,
+
+    // Replaced by `channels`
+    #[serde(default)]
+    channel_id: Option<u64>,
+    #[serde(default)]
+    posts: Vec<(u64, i64)>,
+    #[serde(default)]
+    notifications: Vec<u64>,
+    #[serde(default)]
+    ocean_channel_id: Option<u64>,
+    #[serde(default)]
+    ocean_posts: Vec<u64>,
+}
+
+impl From<StoredGuildItem> for GuildItem {
+    fn from(stored: StoredGuildItem) -> Self {
+        let mut guild = GuildItem {
+            channels: stored.channels,
+            role_id: stored.role_id,
+            ocean_role_id: stored.ocean_role_id,
+            trackers: stored.trackers,
+            roles: stored.roles,
+            lead_times: stored.lead_times,
+            endings: stored.endings,
+            tiers: stored.tiers,
+        };
+
+        if let Some(channel_id) = stored.channel_id {
+            guild.bind(channel_id, &[ChannelContent::Board, ChannelContent::Alerts]);
+        }
+        if let Some(channel_id) = stored.ocean_channel_id {
+            guild.bind(channel_id, &[ChannelContent::Ocean, ChannelContent::OceanAlerts]);
+        }
+
+        // Keep cleaning up messages posted before the migration
+        for binding in guild.channels.iter_mut() {
+            if Some(binding.channel_id) == stored.channel_id {
+                binding.posts.extend(&stored.posts);
+                binding.notifications.extend(&stored.notifications);
+            }
+            if Some(binding.channel_id) == stored.ocean_channel_id {
+                binding.ocean_posts.extend(&stored.ocean_posts);
+            }
+        }
+
+        guild
+    }
+}
+
+/// A user's request for a DM ahead of a tracker's windows, stored under the "reminders" key
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct Reminder {
+    pub tracker: Tracker,
+    /// Seconds before the window starts
+    pub lead: i64,
+    /// Start of the last window reminded about
+    #[serde(default)]
+    pub last_sent: Option<i64>,
+}
+
+impl Reminder {
+    pub fn new(tracker: Tracker, lead: Duration) -> Self {
+        Reminder { tracker, lead: lead.num_seconds(), last_sent: None }
+    }
+
+    /// How long before the window to send the reminder, or None if out of range
+    pub fn lead(&self) -> Option<Duration> {
+        stored_lead(self.lead)
+    }
+
+    /// Finds the window this reminder should be sent for, if it is due and not yet sent
+    /// Reminders belong to users rather than guilds, so ocean voyages are judged by the default tiers
+    pub fn due(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let start = self.tracker.next_start(now, &TierConfig::default())?;
+        let due = start.checked_sub_signed(self.lead()?)? <= now && self.last_sent != Some(start.timestamp());
+        due.then_some(start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ocean::{Route, RouteTier};
+
+    fn guild() -> GuildItem {
+        let mut guild = GuildItem::default();
+        guild.bind(1, &[ChannelContent::Board, ChannelContent::Alerts]);
+        guild.bind(2, &[ChannelContent::Ocean]);
+        guild.channels[0].posts.push((10, 0));
+        guild.channels[0].alerts.push((11, 0));
+        guild.channels[1].ocean_posts.push(20);
+        guild
+    }
+
+    #[test]
+    fn unbind_returns_messages_of_dropped_bindings() {
+        let mut guild = guild();
+        assert_eq!(guild.unbind(None, &[ChannelContent::Board, ChannelContent::Alerts]), vec![(1, 10), (1, 11)]);
+        assert_eq!(guild.channels.len(), 1);
+    }
+
+    #[test]
+    fn unbind_keeps_messages_of_remaining_bindings() {
+        let mut guild = guild();
+        assert_eq!(guild.unbind(Some(1), &[ChannelContent::Alerts]), vec![]);
+        assert_eq!(guild.channels[0].messages(), vec![10, 11]);
+    }
+
+    #[test]
+    fn oversized_stored_leads_are_skipped() {
+        let mut guild = GuildItem { lead_times: vec![i64::MAX, 10 * 365 * 24 * 3600, 600], ..Default::default() };
+        assert_eq!(guild.lead_times(), vec![Duration::minutes(10)]);
+
+        guild.lead_times = vec![i64::MAX];
+        assert_eq!(guild.lead_times(), vec![Duration::minutes(DEFAULT_LEAD_MINUTES)]);
+    }
+
+    #[test]
+    fn oversized_reminder_leads_are_never_due() {
+        let now = Utc::now();
+        let reminder = Reminder { tracker: Tracker::Crab, lead: i64::MAX, last_sent: None };
+        assert_eq!(reminder.lead(), None);
+        assert_eq!(reminder.due(now), None);
+
+        let reminder = Reminder { lead: Duration::hours(MAX_DURATION_HOURS).num_seconds() + 1, ..reminder };
+        assert_eq!(reminder.due(now), None);
+    }
+
+    #[test]
+    fn oversized_ending_leads_are_skipped() {
+        let mut guild = GuildItem::default();
+        guild.set_ending(Tracker::Crab, Some(Duration::minutes(10)));
+        guild.endings.push((Tracker::Skoll, i64::MAX));
+
+        assert_eq!(guild.ending_lead(Tracker::Crab), Some(Duration::minutes(10)));
+        assert_eq!(guild.ending_lead(Tracker::Skoll), None);
+    }
+
+    #[test]
+    fn unbind_returns_status_message_and_pings() {
+        let mut guild = GuildItem::default();
+        guild.bind(1, &[ChannelContent::Status]);
+        guild.channels[0].status_id = Some(30);
+        guild.channels[0].pings.push(31);
+
+        assert_eq!(guild.unbind(Some(1), &[ChannelContent::Status]), vec![(1, 30), (1, 31)]);
+        assert!(guild.channels.is_empty());
+    }
+
+    #[test]
+    fn rebind_to_new_channel_drops_old_binding() {
+        let mut guild = guild();
+        assert_eq!(guild.rebind(3, &[ChannelContent::Board, ChannelContent::Alerts]), vec![(1, 10), (1, 11)]);
+        assert!(guild.channels_with(ChannelContent::Board).all(|binding| binding.channel_id == 3));
+    }
+
+    #[test]
+    fn rebind_to_same_channel_keeps_messages() {
+        let mut guild = guild();
+        assert_eq!(guild.rebind(1, &[ChannelContent::Board, ChannelContent::Alerts]), vec![]);
+        assert_eq!(guild.channels[0].messages(), vec![10, 11]);
+    }
+
+    #[test]
+    fn tiers_are_saved() {
+        let mut guild = guild();
+        guild.tiers.threshold = RouteTier::Best;
+        guild.tiers.overrides.insert(Route::RhotanoNight, RouteTier::Bad);
+
+        let saved: GuildItem = serde_json::from_str(&serde_json::to_string(&guild).unwrap()).unwrap();
+        assert_eq!(saved.tiers, guild.tiers);
+    }
+
+    #[test]
+    fn older_guilds_get_default_tiers() {
+        let saved: GuildItem = serde_json::from_str(r#"{"role_id": null}"#).unwrap();
+        assert_eq!(saved.tiers, TierConfig::default());
+    }
+}
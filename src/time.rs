@@ -1,6 +1,7 @@
 use core::ops::{Add, Sub};
 use chrono::{Duration, DateTime, TimeZone};
 use chrono::prelude::*;
+use crate::datetime_eorzea::DateTimeEorzea;
 
 pub trait TruncateDateTime: Sized {
     /// Rounds time down to the nearest duration
@@ -17,4 +18,264 @@ impl<Tz: TimeZone> TruncateDateTime for DateTime<Tz> {
     fn truncate(&mut self, duration: Duration) {
         *self = self.clone().sub(Duration::seconds(self.timestamp() % duration.num_seconds()));
     }
-}
\ No newline at end of file
+}
+
+/// Longest duration `parse_duration` accepts, in hours
+/// Leads are only ever needed a cycle or so ahead, and anything much larger overflows date arithmetic
+pub const MAX_DURATION_HOURS: i64 = 24;
+
+/// Reads a short duration such as "10m", "2h" or "1h30m", where a bare number means minutes
+/// Returns None for anything longer than `MAX_DURATION_HOURS`
+pub fn parse_duration(text: &str) -> Option<Duration> {
+    let text = text.trim().to_lowercase();
+    let within_cap = |duration: Duration| duration <= Duration::hours(MAX_DURATION_HOURS);
+    if let Ok(minutes) = text.parse::<i64>() {
+        return Duration::try_minutes(minutes).filter(|duration| within_cap(*duration));
+    }
+
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    for c in text.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'h' | 'm' | 's' => {
+                let value = number.parse::<i64>().ok()?;
+                let duration = match c {
+                    'h' => Duration::try_hours(value)?,
+                    'm' => Duration::try_minutes(value)?,
+                    _ => Duration::try_seconds(value)?,
+                };
+                total = total.checked_add(&duration).filter(|total| within_cap(*total))?;
+                number.clear();
+            }
+            _ => return None,
+        }
+    }
+
+    match number.is_empty() && !text.is_empty() {
+        true => Some(total),
+        false => None,
+    }
+}
+
+/// Reads a list of durations such as "30m, 10m, 2m", longest first
+/// Returns None if any are invalid or not positive
+pub fn parse_durations(list: &str) -> Option<Vec<Duration>> {
+    let mut durations: Vec<Duration> = list.split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|text| !text.is_empty())
+        .map(|text| parse_duration(text).filter(|duration| *duration > Duration::zero()))
+        .collect::<Option<_>>()?;
+    durations.sort_unstable_by(|a, b| b.cmp(a));
+    durations.dedup();
+    Some(durations)
+}
+
+/// Writes a duration the way `parse_duration` reads it, e.g. "1h30m"
+pub fn format_duration(duration: Duration) -> String {
+    let hours = duration.num_hours();
+    let minutes = duration.num_minutes() % 60;
+    let seconds = duration.num_seconds() % 60;
+
+    let mut text = String::new();
+    if hours > 0 {
+        text += &format!("{hours}h");
+    }
+    if minutes > 0 {
+        text += &format!("{minutes}m");
+    }
+    if seconds > 0 || text.is_empty() {
+        text += &format!("{seconds}s");
+    }
+    text
+}
+
+/// Reads a clock time such as "18:00" or "18"
+pub fn parse_clock(text: &str) -> Option<(u32, u32)> {
+    let (hour, minute) = text.trim().split_once(':').unwrap_or((text.trim(), "0"));
+    let (hour, minute) = (hour.parse::<u32>().ok()?, minute.parse::<u32>().ok()?);
+    (hour < 24 && minute < 60).then_some((hour, minute))
+}
+
+/// Bare numbers shorter than this are too small to be a recent UNIX timestamp, and more likely meant as a clock time
+const TIMESTAMP_DIGITS: usize = 9;
+
+/// Latest UNIX timestamp accepted, the start of the year 3000
+/// Much later and the Eorzean time overflows converting to a date or a weather seed
+const LATEST_TIMESTAMP: i64 = 32_503_680_000;
+
+/// Reads a moment such as a UNIX timestamp, a Discord `<t:...>` token, an ISO date or date and time in UTC,
+/// or an Eorzean clock time like "ET 18:00", optionally followed by "tomorrow" to start from the next UTC day
+/// Returns None for anything before 1970 or from the year 3000 on
+pub fn parse_time(text: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    read_time(text, now).filter(|time| (0..LATEST_TIMESTAMP).contains(&time.timestamp()))
+}
+
+fn read_time(text: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let text = text.trim();
+
+    let timestamp = match text.strip_prefix("<t:").and_then(|token| token.strip_suffix('>')) {
+        Some(token) => token.split(':').next(),
+        None => Some(text).filter(|text| text.len() >= TIMESTAMP_DIGITS),
+    };
+    if let Some(Ok(timestamp)) = timestamp.map(str::parse::<i64>) {
+        return Utc.timestamp_opt(timestamp, 0).single();
+    }
+
+    if let Ok(dt) = DateTime::parse_from_rfc3339(text) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"] {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(text, format) {
+            return Some(Utc.from_utc_datetime(&dt));
+        }
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        return Some(Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0)?));
+    }
+
+    let words: Vec<String> = text.split_whitespace().map(|word| word.to_lowercase()).collect();
+    let (clock, from) = match &words[..] {
+        [et, clock] if et == "et" => (clock, now),
+        [et, clock, day] if et == "et" && day == "today" => (clock, now),
+        [et, clock, day] if et == "et" && day == "tomorrow" => (clock, Utc.from_utc_datetime(&now.date_naive().succ_opt()?.and_hms_opt(0, 0, 0)?)),
+        _ => return None,
+    };
+    let (hour, minute) = parse_clock(clock)?;
+    let time = DateTimeEorzea::next_clock(from, hour, minute);
+
+    // Round up, so the real second we land on is already at or past the Eorzean time
+    let utc = time.to_utc();
+    match DateTimeEorzea::from_utc(utc) < time {
+        true => Some(utc + Duration::seconds(1)),
+        false => Some(utc),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_duration_units() {
+        assert_eq!(parse_duration("10m"), Some(Duration::minutes(10)));
+        assert_eq!(parse_duration("2h"), Some(Duration::hours(2)));
+        assert_eq!(parse_duration("90s"), Some(Duration::seconds(90)));
+        assert_eq!(parse_duration("1h30m"), Some(Duration::minutes(90)));
+        assert_eq!(parse_duration(" 1H5M10S "), Some(Duration::seconds(3910)));
+    }
+
+    #[test]
+    fn parse_duration_bare_number_is_minutes() {
+        assert_eq!(parse_duration("15"), Some(Duration::minutes(15)));
+    }
+
+    #[test]
+    fn parse_duration_rejects_invalid() {
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("m"), None);
+        assert_eq!(parse_duration("5x"), None);
+        assert_eq!(parse_duration("1.5h"), None);
+        assert_eq!(parse_duration("10m5"), None);
+        assert_eq!(parse_duration("ten minutes"), None);
+    }
+
+    #[test]
+    fn parse_duration_rejects_too_long() {
+        assert_eq!(parse_duration("24h"), Some(Duration::hours(24)));
+        assert_eq!(parse_duration("1440"), Some(Duration::hours(24)));
+        assert_eq!(parse_duration("24h1s"), None);
+        assert_eq!(parse_duration("1441"), None);
+        assert_eq!(parse_duration("9999999999999999m"), None);
+        assert_eq!(parse_duration("9999999999999999"), None);
+        assert_eq!(parse_duration("99999999999999999999h"), None);
+        assert_eq!(parse_durations("30m 25h"), None);
+    }
+
+    #[test]
+    fn parse_durations_sorts_longest_first() {
+        assert_eq!(parse_durations("2m, 30m 10m"), Some(vec![Duration::minutes(30), Duration::minutes(10), Duration::minutes(2)]));
+        assert_eq!(parse_durations("5m,5,1h"), Some(vec![Duration::hours(1), Duration::minutes(5)]));
+        assert_eq!(parse_durations(""), Some(vec![]));
+    }
+
+    #[test]
+    fn parse_durations_rejects_invalid() {
+        assert_eq!(parse_durations("5m, 0"), None);
+        assert_eq!(parse_durations("5m, -1"), None);
+        assert_eq!(parse_durations("5m, soon"), None);
+    }
+
+    #[test]
+    fn format_duration_round_trips() {
+        for text in ["1h30m", "45s", "2h", "10m5s"] {
+            assert_eq!(format_duration(parse_duration(text).unwrap()), text);
+        }
+        assert_eq!(format_duration(Duration::zero()), "0s");
+    }
+
+    #[test]
+    fn parse_time_timestamps() {
+        let now = Utc::now();
+        assert_eq!(parse_time("1700000000", now), "2023-11-14T22:13:20Z".parse().ok());
+        assert_eq!(parse_time("<t:1700000000:R>", now), "2023-11-14T22:13:20Z".parse().ok());
+        assert_eq!(parse_time("<t:18>", now), "1970-01-01T00:00:18Z".parse().ok());
+    }
+
+    #[test]
+    fn parse_time_short_numbers_are_not_timestamps() {
+        assert_eq!(parse_time("18", Utc::now()), None);
+        assert_eq!(parse_time("1800", Utc::now()), None);
+    }
+
+    #[test]
+    fn parse_time_rejects_out_of_range() {
+        let now = Utc::now();
+        assert_eq!(parse_time("200000000000", now), None);
+        assert_eq!(parse_time("999999999999", now), None);
+        assert_eq!(parse_time("<t:-99999999999>", now), None);
+        assert_eq!(parse_time("<t:-1>", now), None);
+        assert_eq!(parse_time("3000-01-01", now), None);
+        assert_eq!(parse_time("2999-12-31", now), "2999-12-31T00:00:00Z".parse().ok());
+
+        // The latest time accepted still has weather
+        let latest = Utc.timestamp_opt(LATEST_TIMESTAMP - 1, 0).unwrap();
+        DateTimeEorzea::from_utc(latest).to_weather_rate();
+    }
+
+    #[test]
+    fn parse_time_dates() {
+        let now = Utc::now();
+        assert_eq!(parse_time("2024-06-01 18:00", now), "2024-06-01T18:00:00Z".parse().ok());
+        assert_eq!(parse_time("2024-06-01", now), "2024-06-01T00:00:00Z".parse().ok());
+        assert_eq!(parse_time("2024-06-01T18:00:00+02:00", now), "2024-06-01T16:00:00Z".parse().ok());
+    }
+
+    #[test]
+    fn parse_time_eorzean_clock() {
+        let now: DateTime<Utc> = "2024-06-01T00:00:00Z".parse().unwrap();
+        let time = parse_time("ET 18:00", now).unwrap();
+
+        assert!(time >= now);
+        assert_eq!(DateTimeEorzea::from_utc(time).format("%H:%M").to_string(), "18:00");
+        assert!(parse_time("ET 18:00 tomorrow", now).unwrap() >= "2024-06-02T00:00:00Z".parse::<DateTime<Utc>>().unwrap());
+        assert_eq!(parse_time("ET 25:00", now), None);
+    }
+
+    #[test]
+    fn parse_clock_times() {
+        assert_eq!(parse_clock("18:00"), Some((18, 0)));
+        assert_eq!(parse_clock("18"), Some((18, 0)));
+        assert_eq!(parse_clock(" 7:05 "), Some((7, 5)));
+        assert_eq!(parse_clock("0:59"), Some((0, 59)));
+    }
+
+    #[test]
+    fn parse_clock_rejects_invalid() {
+        assert_eq!(parse_clock("24:00"), None);
+        assert_eq!(parse_clock("18:60"), None);
+        assert_eq!(parse_clock("18:"), None);
+        assert_eq!(parse_clock(":30"), None);
+        assert_eq!(parse_clock("-1"), None);
+        assert_eq!(parse_clock("six"), None);
+    }
+}
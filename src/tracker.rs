@@ -0,0 +1,331 @@
+use crate::datetime_eorzea::DateTimeEorzea;
+use crate::ocean::{voyages, Route, TierConfig};
+use crate::status::*;
+use crate::time::*;
+use crate::weather::{EorzeaMap, EorzeaWeather};
+use crate::window::{past_weather_cycles, weather_cycles, weather_windows, Window};
+use chrono::{DateTime, Duration, Utc};
+use derive_more::{Deref, DerefMut};
+
+/// Something a guild can follow in its posts
+#[derive(serde::Serialize, serde::Deserialize, Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum Tracker {
+    Crab,
+    Cassie,
+    Skoll,
+    Pagos,
+    Pyros,
+    Hydatos,
+    /// Every ocean fishing voyage, pinging for notable routes
+    Ocean,
+    /// A single ocean fishing route, always pinged
+    OceanRoute(Route),
+}
+
+impl Tracker {
+    /// Every tracker other than the individual ocean routes
+    pub const ALL: [Tracker; 7] = [
+        Tracker::Crab, Tracker::Cassie, Tracker::Skoll,
+        Tracker::Pagos, Tracker::Pyros, Tracker::Hydatos,
+        Tracker::Ocean,
+    ];
+
+    /// The notorious monsters, whose windows open at the start of a weather cycle
+    pub const NMS: [Tracker; 3] = [Tracker::Crab, Tracker::Cassie, Tracker::Skoll];
+
+    /// Finds a tracker by name, e.g. "Crab" or an ocean route such as "MerlthorNight"
+    pub fn from_name(name: &str) -> Option<Self> {
+        Tracker::ALL.into_iter()
+            .find(|tracker| tracker.to_name().eq_ignore_ascii_case(name))
+            .or_else(|| Route::from_name(name).map(Tracker::OceanRoute))
+    }
+
+    pub fn to_name(self) -> String {
+        match self {
+            Tracker::OceanRoute(route) => format!("{route:?}"),
+            _ => format!("{self:?}"),
+        }
+    }
+
+    /// Finds the start of this tracker's next window after `from`, counting only voyages `tiers` finds notable for Ocean
+    /// Zones have no windows of their own, only weather
+    pub fn next_start(self, from: DateTime<Utc>, tiers: &TierConfig) -> Option<DateTime<Utc>> {
+        let cycle = DateTimeEorzea::from_utc(from).truncated(Duration::hours(8));
+
+        let start = match self {
+            Tracker::Crab => crab_status(cycle, Direction::Future).to_utc(),
+            Tracker::Cassie => cassie_status(cycle, Direction::Future).to_utc(),
+            Tracker::Skoll => skoll_status(cycle, Direction::Future).to_utc(),
+            Tracker::Ocean => Route::schedule(from, from + Duration::days(1)).into_iter()
+                .find(|voyage| voyage.departure > from && tiers.is_notable(voyage.route))?
+                .departure,
+            Tracker::OceanRoute(route) => voyages(from, Direction::Future)
+                .filter(|voyage| voyage.departure > from)
+                .find(|voyage| voyage.route == route)?
+                .departure,
+            Tracker::Pagos | Tracker::Pyros | Tracker::Hydatos => return None,
+        };
+        Some(start)
+    }
+
+    /// Every NM whose window opens at the start of the cycle `start`
+    pub fn nms_starting(start: DateTimeEorzea) -> Vec<Tracker> {
+        let prev = (start - Duration::hours(8)).to_utc();
+        Tracker::NMS.into_iter()
+            .filter(|tracker| tracker.next_start(prev, &TierConfig::default()) == Some(start.to_utc()))
+            .collect()
+    }
+
+    /// Finds up to `count` of this tracker's weather windows, the first of which may be in progress at `now`
+    /// Ocean fishing and the zones have none
+    pub fn windows(self, now: DateTimeEorzea, count: usize) -> Vec<TrackerWindow> {
+        let search = match WEATHER_SEARCHES.iter().find(|search| search.tracker == Some(self)) {
+            Some(search) => search,
+            None => return vec![],
+        };
+        weather_windows(&search.zone(), now, count, search.matches)
+            .into_iter()
+            .map(|window| TrackerWindow { tracker: self, weather: search.weather, window })
+            .collect()
+    }
+
+    /// Every tracker name, including each ocean route
+    pub fn names() -> Vec<String> {
+        Tracker::ALL.into_iter()
+            .chain(Route::ALL.into_iter().map(Tracker::OceanRoute))
+            .map(Tracker::to_name)
+            .collect()
+    }
+}
+
+/// A stretch of the weather a tracker waits for
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct TrackerWindow {
+    pub tracker: Tracker,
+    pub weather: &'static str,
+    pub window: Window,
+}
+
+/// Weather worth waiting for, which can be looked up before and after any time
+pub struct WeatherSearch {
+    pub name: &'static str,
+    /// The tracker this weather spawns, if any
+    pub tracker: Option<Tracker>,
+    pub weather: &'static str,
+    zone: &'static str,
+    matches: fn(&EorzeaWeather) -> bool,
+    /// Windows shorter than this many cycles don't count
+    min_cycles: usize,
+}
+
+pub const WEATHER_SEARCHES: [WeatherSearch; 5] = [
+    WeatherSearch { name: "Crab", tracker: Some(Tracker::Crab), weather: "Fog", zone: "Eureka Pagos", matches: |weather| weather.name == "Fog", min_cycles: 1 },
+    WeatherSearch { name: "Cassie", tracker: Some(Tracker::Cassie), weather: "Blizzards", zone: "Eureka Pagos", matches: |weather| weather.name == "Blizzards", min_cycles: 1 },
+    WeatherSearch { name: "Skoll", tracker: Some(Tracker::Skoll), weather: "Blizzards", zone: "Eureka Pyros", matches: |weather| weather.name == "Blizzards", min_cycles: 1 },
+    WeatherSearch { name: "Hotbox", tracker: None, weather: "Snow, Blizzards or Umbral Wind", zone: "Eureka Pyros", matches: |weather| is_hotbox_weather(weather.clone()), min_cycles: 2 },
+    WeatherSearch { name: "Offensive", tracker: None, weather: "Snow", zone: "Eureka Hydatos", matches: |weather| weather.name == "Snow", min_cycles: 2 },
+];
+
+impl WeatherSearch {
+    pub fn from_name(name: &str) -> Option<&'static Self> {
+        WEATHER_SEARCHES.iter().find(|search| search.name.eq_ignore_ascii_case(name.trim()))
+    }
+
+    fn zone(&self) -> EorzeaMap {
+        EorzeaMap::from_name(self.zone).expect("Could not find map")
+    }
+
+    /// Finds the first long enough run of matching cycles
+    fn first_run(&self, cycles: impl Iterator<Item = (Window, EorzeaWeather)>) -> Option<Window> {
+        let mut run: Option<Window> = None;
+        let mut length = 0;
+
+        for (cycle, weather) in cycles {
+            if (self.matches)(&weather) {
+                run = Some(match run {
+                    Some(run) => Window::new(run.start.min(cycle.start), run.end.max(cycle.end)),
+                    None => cycle,
+                });
+                length += 1;
+            } else if length >= self.min_cycles {
+                return run;
+            } else {
+                run = None;
+                length = 0;
+            }
+        }
+        None
+    }
+
+    /// The start of the run of matching cycles in progress at `now`, or the current cycle if there is none
+    fn run_start(&self, zone: &EorzeaMap, now: DateTimeEorzea) -> DateTimeEorzea {
+        let mut start = now.truncated(Duration::hours(8));
+        if (self.matches)(&zone.weather(start)) {
+            while (self.matches)(&zone.weather(start - Duration::hours(8))) {
+                start -= Duration::hours(8);
+            }
+        }
+        start
+    }
+
+    /// The window in progress at `now`, or the next one to begin
+    pub fn next(&self, now: DateTimeEorzea) -> Option<Window> {
+        let zone = self.zone();
+        self.first_run(weather_cycles(&zone, self.run_start(&zone, now)))
+    }
+
+    /// The last window to have ended by `now`
+    pub fn previous(&self, now: DateTimeEorzea) -> Option<Window> {
+        let zone = self.zone();
+        self.first_run(past_weather_cycles(&zone, self.run_start(&zone, now)))
+    }
+}
+
+/// The trackers a guild follows
+#[derive(serde::Serialize, serde::Deserialize, Deref, DerefMut, Clone, Eq, PartialEq, Debug, Default)]
+pub struct Trackers(pub Vec<Tracker>);
+
+impl Trackers {
+    /// Checks if a tracker is followed, where following nothing means following everything
+    pub fn follows(&self, tracker: Tracker) -> bool {
+        self.is_empty() || self.contains(&tracker)
+    }
+
+    /// Starts following a tracker, listing every tracker first if everything was followed
+    /// Returns false without changing anything if it was already listed
+    pub fn follow(&mut self, tracker: Tracker) -> bool {
+        let mut followed: Vec<Tracker> = match self.is_empty() {
+            true => Tracker::ALL.to_vec(),
+            false => self.0.clone(),
+        };
+        if followed.contains(&tracker) {
+            return false;
+        }
+        followed.push(tracker);
+        self.0 = followed;
+        true
+    }
+
+    /// Stops following a tracker, listing every other tracker if everything was followed
+    /// Returns false without changing anything if this would leave nothing followed, which would mean following everything again
+    pub fn unfollow(&mut self, tracker: Tracker) -> bool {
+        let remaining: Vec<Tracker> = match self.is_empty() {
+            true => Tracker::ALL.into_iter().filter(|followed| *followed != tracker).collect(),
+            false => self.iter().copied().filter(|followed| *followed != tracker).collect(),
+        };
+        if remaining.is_empty() {
+            return false;
+        }
+        self.0 = remaining;
+        true
+    }
+
+    /// Checks if an ocean fishing route belongs on the board
+    pub fn follows_route(&self, route: Route) -> bool {
+        self.follows(Tracker::Ocean) || self.contains(&Tracker::OceanRoute(route))
+    }
+
+    /// Checks if an ocean fishing route is worth a ping
+    pub fn pings_route(&self, route: Route, tiers: &TierConfig) -> bool {
+        (self.follows(Tracker::Ocean) && tiers.is_notable(route)) || self.contains(&Tracker::OceanRoute(route))
+    }
+}
+
+/// Roles to ping for individual trackers
+#[derive(serde::Serialize, serde::Deserialize, Deref, DerefMut, Clone, Eq, PartialEq, Debug, Default)]
+pub struct TrackerRoles(pub Vec<(Tracker, u64)>);
+
+impl TrackerRoles {
+    pub fn get(&self, tracker: Tracker) -> Option<u64> {
+        self.iter().find(|(mapped, _)| *mapped == tracker).map(|(_, role_id)| *role_id)
+    }
+
+    pub fn set(&mut self, tracker: Tracker, role_id: u64) {
+        self.unset(tracker);
+        self.push((tracker, role_id));
+    }
+
+    pub fn unset(&mut self, tracker: Tracker) {
+        self.retain(|(mapped, _)| *mapped != tracker);
+    }
+
+    /// Picks the roles to ping for a set of upcoming trackers
+    /// Trackers without a role of their own fall back to `fallback`
+    pub fn mentions(&self, upcoming: &[Tracker], fallback: Option<u64>) -> Vec<u64> {
+        let mut roles = vec![];
+        for role_id in upcoming.iter().filter_map(|tracker| self.get(*tracker).or(fallback)) {
+            if !roles.contains(&role_id) {
+                roles.push(role_id);
+            }
+        }
+        roles
+    }
+
+    /// Picks the role to ping for a voyage, preferring the route's own role over the ocean fishing role
+    pub fn route_mention(&self, route: Route, fallback: Option<u64>) -> Option<u64> {
+        self.get(Tracker::OceanRoute(route)).or_else(|| self.get(Tracker::Ocean)).or(fallback)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn follow_when_following_everything() {
+        let mut trackers = Trackers::default();
+        assert!(!trackers.follow(Tracker::Crab));
+        assert_eq!(trackers, Trackers::default());
+
+        let route = Tracker::OceanRoute(Route::ALL[0]);
+        assert!(trackers.follow(route));
+        assert!(Tracker::ALL.into_iter().all(|tracker| trackers.contains(&tracker)));
+        assert!(trackers.contains(&route));
+    }
+
+    #[test]
+    fn follow_adds_to_list() {
+        let mut trackers = Trackers(vec![Tracker::Skoll]);
+        assert!(trackers.follow(Tracker::Crab));
+        assert!(!trackers.follow(Tracker::Crab));
+        assert_eq!(trackers, Trackers(vec![Tracker::Skoll, Tracker::Crab]));
+    }
+
+    #[test]
+    fn unfollow_when_following_everything() {
+        let mut trackers = Trackers::default();
+        assert!(trackers.unfollow(Tracker::Crab));
+
+        assert!(!trackers.follows(Tracker::Crab));
+        assert!(Tracker::ALL.into_iter().filter(|tracker| *tracker != Tracker::Crab).all(|tracker| trackers.follows(tracker)));
+    }
+
+    #[test]
+    fn unfollow_from_list() {
+        let mut trackers = Trackers(vec![Tracker::Crab, Tracker::Skoll]);
+        assert!(trackers.unfollow(Tracker::Crab));
+        assert_eq!(trackers, Trackers(vec![Tracker::Skoll]));
+    }
+
+    #[test]
+    fn unfollow_last_tracker_is_refused() {
+        let mut trackers = Trackers(vec![Tracker::Skoll]);
+        assert!(!trackers.unfollow(Tracker::Skoll));
+        assert_eq!(trackers, Trackers(vec![Tracker::Skoll]));
+    }
+
+    #[test]
+    fn nms_starting_matches_their_next_window() {
+        let mut cycle = DateTimeEorzea::from_utc("2024-01-01T00:00:00Z".parse().unwrap()).truncated(Duration::hours(8));
+        let crab = crab_status(cycle, Direction::Future);
+
+        let mut found = false;
+        while cycle <= crab {
+            let starting = Tracker::nms_starting(cycle);
+            assert_eq!(starting.contains(&Tracker::Crab), cycle == crab);
+            found |= starting.contains(&Tracker::Crab);
+            cycle += Duration::hours(8);
+        }
+        assert!(found);
+    }
+}
@@ -62,6 +62,35 @@ impl EorzeaMap {
         }
     }
 
+    /// Finds a zone with weather by name, ignoring case
+    /// Falls back to the only zone containing `name`, e.g. "pagos" for "Eureka Pagos"
+    pub fn find(name: &str) -> Option<Self> {
+        let names = EorzeaMap::names();
+        let name = name.trim().to_lowercase();
+
+        let exact = names.iter().find(|zone| zone.to_lowercase() == name);
+        let partial = || {
+            let mut matches = names.iter().filter(|zone| zone.to_lowercase().contains(&name));
+            match (matches.next(), matches.next()) {
+                (Some(zone), None) => Some(zone),
+                _ => None,
+            }
+        };
+        exact.or_else(partial).and_then(|zone| EorzeaMap::from_name(zone))
+    }
+
+    /// Every zone with a complete weather table, without duplicates
+    pub fn names() -> Vec<String> {
+        let mut names: Vec<String> = vec![];
+        for map in MAP_INFO.iter().filter(|map| !map.name.is_empty()) {
+            if !names.contains(&map.name) {
+                names.push(map.name.clone());
+            }
+        }
+        names.retain(|name| EorzeaMap::from_name(name).is_some_and(|zone| zone.weathers.iter().any(|weather| weather.rate >= 100)));
+        names
+    }
+
     /// Get this zone's weather
     pub fn weather<W: Into<EorzeaWeatherRate>>(&self, rate: W) -> EorzeaWeather {
         let rate = rate.into().0;
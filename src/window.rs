@@ -0,0 +1,414 @@
+use crate::data::{BellRange, Conditions, WEATHER_NAMES};
+use crate::datetime_eorzea::DateTimeEorzea;
+use crate::time::*;
+use crate::weather::{EorzeaMap, EorzeaWeather};
+use chrono::Duration;
+
+/// How many weather cycles a search looks through before giving up
+/// Roughly 30 real days, enough to cover even the rarest weather patterns
+pub const SEARCH_LIMIT: usize = 1850;
+
+/// A span of Eorzean time during which some condition holds
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Window {
+    pub start: DateTimeEorzea,
+    pub end: DateTimeEorzea,
+}
+
+impl Window {
+    pub fn new(start: DateTimeEorzea, end: DateTimeEorzea) -> Self {
+        Window { start, end }
+    }
+
+    /// Real-time length of this window
+    pub fn duration(&self) -> Duration {
+        self.end.to_utc() - self.start.to_utc()
+    }
+
+    pub fn contains(&self, dt: DateTimeEorzea) -> bool {
+        self.start <= dt && dt < self.end
+    }
+}
+
+/// Walks a zone's weather one 8-hour cycle at a time, starting with the cycle containing `now`
+pub fn weather_cycles(zone: &EorzeaMap, now: DateTimeEorzea) -> impl Iterator<Item = (Window, EorzeaWeather)> + '_ {
+    let start = now.truncated(Duration::hours(8));
+
+    (0..SEARCH_LIMIT as i64).map(move |i| {
+        let cycle = start + Duration::hours(8 * i);
+        (Window::new(cycle, cycle + Duration::hours(8)), zone.weather(cycle))
+    })
+}
+
+/// Walks back through a zone's weather one 8-hour cycle at a time, starting with the cycle before the one containing `now`
+pub fn past_weather_cycles(zone: &EorzeaMap, now: DateTimeEorzea) -> impl Iterator<Item = (Window, EorzeaWeather)> + '_ {
+    let start = now.truncated(Duration::hours(8));
+
+    (1..SEARCH_LIMIT as i64).map(move |i| {
+        let cycle = start - Duration::hours(8 * i);
+        (Window::new(cycle, cycle + Duration::hours(8)), zone.weather(cycle))
+    })
+}
+
+/// Adds a window, merging it into the last one if they are back-to-back
+/// Returns false once `count` windows are known to be complete
+fn push_window(windows: &mut Vec<Window>, window: Window, count: usize) -> bool {
+    match windows.last_mut() {
+        Some(last) if last.end == window.start => last.end = window.end,
+        _ => {
+            if windows.len() == count {
+                return false;
+            }
+            windows.push(window);
+        }
+    }
+    true
+}
+
+/// Finds up to `count` windows of back-to-back weather cycles matching the predicate
+/// The first window may already be in progress at `now`
+pub fn weather_windows<F>(zone: &EorzeaMap, now: DateTimeEorzea, count: usize, mut predicate: F) -> Vec<Window>
+    where
+        F: FnMut(&EorzeaWeather) -> bool,
+{
+    let mut windows = vec![];
+
+    for (cycle, weather) in weather_cycles(zone, now) {
+        if predicate(&weather) && !push_window(&mut windows, cycle, count) {
+            break;
+        }
+    }
+
+    windows
+}
+
+impl BellRange {
+    /// Reads a range of bells such as "18-22" or "ET 18:00-22:00"
+    pub fn from_text(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let text = text.strip_prefix("ET ").or_else(|| text.strip_prefix("et ")).unwrap_or(text);
+        let (start, end) = text.split_once('-')?;
+
+        match (parse_clock(start)?, parse_clock(end)?) {
+            ((start, 0), (end, 0)) if start != end => Some(BellRange { start, end }),
+            _ => None,
+        }
+    }
+
+    /// Finds the next `count` times this range comes around, the first of which may be in progress at `now`
+    pub fn windows(self, now: DateTimeEorzea, count: usize) -> Vec<Window> {
+        let length = (self.end + 24 - self.start) % 24;
+        let yesterday = now.truncated(Duration::hours(24)) - Duration::hours(24);
+
+        (0..)
+            .map(|day| yesterday + Duration::hours(24 * day + self.start as i64))
+            .map(|start| Window::new(start, start + Duration::hours(length as i64)))
+            .filter(|window| window.end > now)
+            .take(count)
+            .collect()
+    }
+
+    /// Splits a weather cycle into the parts that fall within this range
+    pub fn clip(self, cycle: Window) -> Vec<Window> {
+        let day = cycle.start.truncated(Duration::hours(24));
+        let bell = |hour: u32| day + Duration::hours(hour as i64);
+
+        let ranges = if self.start <= self.end {
+            vec![(bell(self.start), bell(self.end))]
+        } else {
+            vec![(bell(0), bell(self.end)), (bell(self.start), bell(24))]
+        };
+
+        ranges.into_iter()
+            .map(|(start, end)| Window::new(start.max(cycle.start), end.min(cycle.end)))
+            .filter(|window| window.start < window.end)
+            .collect()
+    }
+
+    pub fn contains(self, bell: u32) -> bool {
+        if self.start <= self.end {
+            self.start <= bell && bell < self.end
+        } else {
+            self.start <= bell || bell < self.end
+        }
+    }
+}
+
+impl Conditions {
+    /// Describes why these conditions could never be searched, if they can't be
+    pub fn problem(&self) -> Option<String> {
+        let zone = match EorzeaMap::from_name(&self.zone) {
+            Some(zone) if zone.weathers.is_empty() => return Some(format!("zone `{}` has no weather", self.zone)),
+            Some(zone) => zone,
+            None => return Some(format!("unknown zone `{}`", self.zone)),
+        };
+
+        // Several weather IDs can share a name, so compare names rather than IDs
+        for name in self.weathers.iter().chain(&self.previous_weathers) {
+            if EorzeaWeather::from_name(name).is_none() {
+                return Some(format!("unknown weather `{name}`"));
+            }
+            if !zone.weathers.iter().any(|rate| WEATHER_NAMES.get(&rate.weather_id).is_some_and(|weather| weather.en == *name)) {
+                return Some(format!("`{name}` never happens in {}", self.zone));
+            }
+        }
+
+        match self.bells {
+            Some(bells) if bells.start >= 24 || bells.end > 24 => Some(format!("bells {}-{} are out of range", bells.start, bells.end)),
+            _ => None,
+        }
+    }
+
+    /// Checks a weather cycle against these conditions, ignoring bells
+    pub fn matches_weather(&self, previous: &EorzeaWeather, current: &EorzeaWeather) -> bool {
+        (self.weathers.is_empty() || self.weathers.contains(&current.name))
+            && (self.previous_weathers.is_empty() || self.previous_weathers.contains(&previous.name))
+    }
+
+    /// Finds up to `count` windows where these conditions hold
+    /// The first window may already be in progress at `now`
+    pub fn windows(&self, now: DateTimeEorzea, count: usize) -> Vec<Window> {
+        self.search(now, count, |_| true)
+    }
+
+    /// Finds every window where these conditions hold, starting before `until`
+    pub fn windows_until(&self, now: DateTimeEorzea, until: DateTimeEorzea) -> Vec<Window> {
+        self.search(now, usize::MAX, |cycle| cycle.start < until)
+            .into_iter()
+            .filter(|window| window.start < until)
+            .collect()
+    }
+
+    fn search<F>(&self, now: DateTimeEorzea, count: usize, mut predicate: F) -> Vec<Window>
+        where
+            F: FnMut(&Window) -> bool,
+    {
+        let zone = EorzeaMap::from_name(&self.zone).expect("Could not find map");
+        let mut windows = vec![];
+        let mut previous = zone.weather(now.truncated(Duration::hours(8)) - Duration::hours(8));
+
+        'search: for (cycle, weather) in weather_cycles(&zone, now).take_while(|(cycle, _)| predicate(cycle)) {
+            if self.matches_weather(&previous, &weather) {
+                let parts = match self.bells {
+                    Some(bells) => bells.clip(cycle),
+                    None => vec![cycle],
+                };
+
+                for window in parts.into_iter().filter(|window| window.end > now) {
+                    if !push_window(&mut windows, window, count) {
+                        break 'search;
+                    }
+                }
+            }
+            previous = weather;
+        }
+
+        windows
+    }
+
+    /// Finds when the window in progress at `now` actually began, however many cycles ago that was
+    pub fn run_start(&self, now: DateTimeEorzea) -> Option<DateTimeEorzea> {
+        let mut start = self.windows(now, 1).into_iter().next().filter(|window| window.contains(now))?.start;
+
+        // Searches only look back as far as the start of a cycle, so keep stepping over the boundary
+        for _ in 0..SEARCH_LIMIT {
+            match self.windows(start - Duration::seconds(1), 1).into_iter().next() {
+                Some(window) if window.start < start && window.end >= start => start = window.start,
+                _ => break,
+            }
+        }
+        Some(start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Timelike;
+
+    /// An Eorzean time, e.g. "3000-01-01T16:00:00"
+    fn et(text: &str) -> DateTimeEorzea {
+        DateTimeEorzea(text.parse().expect("Invalid time"))
+    }
+
+    fn cycle(start: &str) -> Window {
+        let start = et(start);
+        Window::new(start, start + Duration::hours(8))
+    }
+
+    #[test]
+    fn bell_range_contains() {
+        let bells = BellRange { start: 9, end: 15 };
+        assert!(bells.contains(9));
+        assert!(bells.contains(14));
+        assert!(!bells.contains(15));
+        assert!(!bells.contains(8));
+    }
+
+    #[test]
+    fn bell_range_contains_across_midnight() {
+        let bells = BellRange { start: 22, end: 2 };
+        assert!(bells.contains(22));
+        assert!(bells.contains(23));
+        assert!(bells.contains(0));
+        assert!(bells.contains(1));
+        assert!(!bells.contains(2));
+        assert!(!bells.contains(12));
+        assert!(!bells.contains(21));
+    }
+
+    #[test]
+    fn bell_range_contains_until_midnight() {
+        let bells = BellRange { start: 18, end: 24 };
+        assert!(bells.contains(18));
+        assert!(bells.contains(23));
+        assert!(!bells.contains(0));
+        assert!(!bells.contains(17));
+    }
+
+    #[test]
+    fn clip_within_cycle() {
+        let bells = BellRange { start: 9, end: 15 };
+        assert_eq!(bells.clip(cycle("3000-01-01T08:00:00")), vec![Window::new(et("3000-01-01T09:00:00"), et("3000-01-01T15:00:00"))]);
+        assert_eq!(bells.clip(cycle("3000-01-01T16:00:00")), vec![]);
+    }
+
+    #[test]
+    fn clip_across_midnight() {
+        let bells = BellRange { start: 22, end: 2 };
+        assert_eq!(bells.clip(cycle("3000-01-01T00:00:00")), vec![Window::new(et("3000-01-01T00:00:00"), et("3000-01-01T02:00:00"))]);
+        assert_eq!(bells.clip(cycle("3000-01-01T08:00:00")), vec![]);
+        assert_eq!(bells.clip(cycle("3000-01-01T16:00:00")), vec![Window::new(et("3000-01-01T22:00:00"), et("3000-01-02T00:00:00"))]);
+    }
+
+    #[test]
+    fn clip_into_both_ends_of_a_cycle() {
+        let bells = BellRange { start: 6, end: 2 };
+        assert_eq!(
+            bells.clip(cycle("3000-01-01T00:00:00")),
+            vec![
+                Window::new(et("3000-01-01T00:00:00"), et("3000-01-01T02:00:00")),
+                Window::new(et("3000-01-01T06:00:00"), et("3000-01-01T08:00:00")),
+            ],
+        );
+    }
+
+    #[test]
+    fn clip_until_midnight() {
+        let bells = BellRange { start: 18, end: 24 };
+        assert_eq!(bells.clip(cycle("3000-01-01T16:00:00")), vec![Window::new(et("3000-01-01T18:00:00"), et("3000-01-02T00:00:00"))]);
+        assert_eq!(bells.clip(cycle("3000-01-02T00:00:00")), vec![]);
+    }
+
+    #[test]
+    fn bell_range_from_text() {
+        assert_eq!(BellRange::from_text("18-22"), Some(BellRange { start: 18, end: 22 }));
+        assert_eq!(BellRange::from_text("ET 22:00-02:00"), Some(BellRange { start: 22, end: 2 }));
+        assert_eq!(BellRange::from_text(" et 0-8 "), Some(BellRange { start: 0, end: 8 }));
+    }
+
+    #[test]
+    fn bell_range_from_text_rejects_invalid() {
+        assert_eq!(BellRange::from_text("18"), None);
+        assert_eq!(BellRange::from_text("18-18"), None);
+        assert_eq!(BellRange::from_text("18:30-20"), None);
+        assert_eq!(BellRange::from_text("25-2"), None);
+        assert_eq!(BellRange::from_text("dusk-dawn"), None);
+    }
+
+    #[test]
+    fn bell_range_windows_across_midnight() {
+        let bells = BellRange { start: 22, end: 2 };
+        let windows = bells.windows(et("3000-01-01T23:00:00"), 2);
+        assert_eq!(windows, vec![
+            Window::new(et("3000-01-01T22:00:00"), et("3000-01-02T02:00:00")),
+            Window::new(et("3000-01-02T22:00:00"), et("3000-01-03T02:00:00")),
+        ]);
+    }
+
+    fn conditions(previous_weathers: &[&str], weathers: &[&str], bells: Option<BellRange>) -> Conditions {
+        Conditions {
+            zone: "Eastern La Noscea".into(),
+            weathers: weathers.iter().map(|weather| weather.to_string()).collect(),
+            previous_weathers: previous_weathers.iter().map(|weather| weather.to_string()).collect(),
+            bells,
+        }
+    }
+
+    #[test]
+    fn problem_with_conditions() {
+        let zone = |zone: &str| Conditions { zone: zone.into(), ..conditions(&[], &["Thunder"], None) };
+        assert_eq!(zone("The Ruby Sea").problem(), None);
+        assert_eq!(zone("The Ruby See").problem(), Some("unknown zone `The Ruby See`".into()));
+
+        let weather = |weather: &str| Conditions { zone: "The Ruby Sea".into(), ..conditions(&[], &[weather], None) };
+        assert_eq!(weather("Thunderr").problem(), Some("unknown weather `Thunderr`".into()));
+        assert_eq!(weather("Snow").problem(), Some("`Snow` never happens in The Ruby Sea".into()));
+
+        let bells = BellRange { start: 18, end: 25 };
+        assert_eq!(conditions(&[], &[], Some(bells)).problem(), Some("bells 18-25 are out of range".into()));
+    }
+
+    fn weather_at(dt: DateTimeEorzea) -> String {
+        EorzeaMap::from_name("Eastern La Noscea").unwrap().weather(dt).name
+    }
+
+    #[test]
+    fn previous_weather_must_match() {
+        let conditions = conditions(&["Fog"], &["Clear Skies", "Fair Skies"], None);
+        let windows = conditions.windows(et("3000-01-01T00:00:00"), 5);
+        assert_eq!(windows.len(), 5);
+
+        for window in windows {
+            assert_eq!(window.start, window.start.truncated(Duration::hours(8)), "{window:?}");
+            assert_eq!(weather_at(window.start - Duration::hours(8)), "Fog", "{window:?}");
+            assert!(conditions.weathers.contains(&weather_at(window.start)), "{window:?}");
+        }
+    }
+
+    #[test]
+    fn previous_weather_window_is_one_cycle() {
+        // Only the first clear cycle follows fog, so these windows never run into a second cycle
+        let conditions = conditions(&["Fog"], &["Clear Skies", "Fair Skies"], None);
+
+        for window in conditions.windows(et("3000-01-01T00:00:00"), 5) {
+            assert_eq!(window.duration(), Duration::minutes(23) + Duration::seconds(20), "{window:?}");
+        }
+    }
+
+    #[test]
+    fn window_in_progress_is_found() {
+        let conditions = conditions(&[], &["Fog"], None);
+        let window = conditions.windows(et("3000-01-01T00:00:00"), 1)[0];
+
+        let now = window.start + Duration::hours(1);
+        assert_eq!(conditions.windows(now, 1), vec![window]);
+        assert_eq!(conditions.run_start(now), Some(window.start));
+    }
+
+    #[test]
+    fn bells_and_weather_combine() {
+        let bells = BellRange { start: 20, end: 4 };
+        let conditions = conditions(&[], &["Clear Skies", "Fair Skies"], Some(bells));
+
+        for window in conditions.windows(et("3000-01-01T00:00:00"), 5) {
+            assert!(bells.contains(window.start.hour()), "{window:?}");
+            assert!(conditions.weathers.contains(&weather_at(window.start)), "{window:?}");
+        }
+    }
+
+    #[test]
+    fn windows_until_stops_at_cut_off() {
+        let conditions = conditions(&[], &["Fog"], None);
+        let now = et("3000-01-01T00:00:00");
+        let windows = conditions.windows(now, 3);
+
+        // A window starting before the cut-off is kept whole
+        let until = windows[1].start + Duration::hours(1);
+        assert_eq!(conditions.windows_until(now, until), windows[..2]);
+
+        // One starting exactly at the cut-off is not
+        assert_eq!(conditions.windows_until(now, windows[1].start), windows[..1]);
+        assert_eq!(conditions.windows_until(now, now), vec![]);
+    }
+}
@@ -0,0 +1,126 @@
+use chrono::{DateTime, Duration, Utc};
+use eureka_notify::ocean::*;
+use eureka_notify::status::Direction;
+
+/// The pattern has 144 voyages, one every 2 hours
+fn cycle() -> Duration {
+    Duration::hours(2 * 144)
+}
+
+/// Departures spread across the schedule, including some before the UNIX epoch
+fn departures() -> Vec<DateTime<Utc>> {
+    [
+        "1960-06-15T08:00:00Z",
+        "1969-12-31T20:00:00Z",
+        "1969-12-31T22:00:00Z",
+        "1970-01-01T00:00:00Z",
+        "1970-01-01T02:00:00Z",
+        "2019-10-29T00:00:00Z",
+        "2020-02-29T12:00:00Z",
+        "2021-01-01T00:00:00Z",
+        "2024-02-29T22:00:00Z",
+        "2026-10-19T02:00:00Z",
+    ]
+        .into_iter()
+        .map(|departure| departure.parse().expect("Invalid departure"))
+        .collect()
+}
+
+#[test]
+fn departures_every_two_hours() {
+    for departure in departures() {
+        assert_eq!(Voyage::from_datetime(departure).departure, departure, "departure at {departure}");
+        assert_eq!(Voyage::from_datetime(departure + Duration::minutes(119)).departure, departure, "voyage from {departure}");
+    }
+}
+
+#[test]
+fn route_holds_until_next_departure() {
+    for departure in departures() {
+        let route = Route::from_datetime(departure);
+        let last_second = departure + Duration::hours(2) - Duration::seconds(1);
+
+        assert_eq!(Route::from_datetime(last_second), route, "voyage from {departure}");
+        assert_eq!(Voyage::from_datetime(last_second).departure, departure, "voyage from {departure}");
+    }
+}
+
+#[test]
+fn second_before_departure_is_previous_voyage() {
+    for departure in departures() {
+        let previous = departure - Duration::hours(2);
+
+        assert_eq!(
+            Route::from_datetime(departure - Duration::seconds(1)),
+            Route::from_datetime(previous),
+            "voyage before {departure}",
+        );
+        assert_eq!(Voyage::from_datetime(departure - Duration::seconds(1)).departure, previous);
+    }
+}
+
+#[test]
+fn departures_before_epoch_continue_the_pattern() {
+    // Voyages before 1970 must land on the same routes as those whole cycles later, rather than restarting the pattern
+    for departure in departures().into_iter().filter(|departure| departure.timestamp() < 0) {
+        let later = departure + cycle() * 100;
+
+        assert_eq!(
+            Route::from_datetime(departure),
+            Route::from_datetime(later),
+            "departure at {departure}",
+        );
+    }
+}
+
+#[test]
+fn pattern_repeats_across_epoch() {
+    let epoch: DateTime<Utc> = "1970-01-01T00:00:00Z".parse().unwrap();
+
+    for voyage in voyages(epoch - cycle(), Direction::Future).take(2 * 144) {
+        assert_eq!(Route::from_datetime(voyage.departure + cycle()), voyage.route, "voyage at {}", voyage.departure);
+    }
+}
+
+#[test]
+fn every_route_sails_each_cycle() {
+    let from: DateTime<Utc> = "2024-01-01T00:00:00Z".parse().unwrap();
+    let routes: Vec<Route> = voyages(from, Direction::Future).take(144).map(|voyage| voyage.route).collect();
+
+    for route in Route::ALL {
+        assert_eq!(routes.iter().filter(|&&sailing| sailing == route).count(), 12, "{route:?}");
+    }
+}
+
+/// A snapshot of the schedule as `Route::from_datetime` produces it today: one departure for every route, plus a few
+/// far from the rest. It only catches `OFFSET` or `PATTERN` being changed by accident, not either of them being wrong
+/// to begin with. That needs departures from an outside schedule, which this does not yet have.
+const SCHEDULE_SNAPSHOT: [(&str, Route); 15] = [
+    ("1969-12-31T22:00:00Z", Route::MerlthorNight),
+    ("2019-10-29T00:00:00Z", Route::RothlytSunset),
+    ("2020-12-08T00:00:00Z", Route::RhotanoDay),
+    ("2024-01-01T00:00:00Z", Route::MerlthorSunset),
+    ("2024-01-01T02:00:00Z", Route::RhotanoSunset),
+    ("2024-01-01T04:00:00Z", Route::BloodbrineNight),
+    ("2024-01-01T06:00:00Z", Route::RothlytNight),
+    ("2024-01-01T08:00:00Z", Route::MerlthorNight),
+    ("2024-01-01T10:00:00Z", Route::RhotanoNight),
+    ("2024-01-01T12:00:00Z", Route::BloodbrineDay),
+    ("2024-01-01T14:00:00Z", Route::RothlytDay),
+    ("2024-01-01T16:00:00Z", Route::RhotanoDay),
+    ("2024-01-01T18:00:00Z", Route::BloodbrineSunset),
+    ("2024-01-01T20:00:00Z", Route::RothlytSunset),
+    ("2024-01-02T14:00:00Z", Route::MerlthorDay),
+];
+
+#[test]
+fn schedule_matches_snapshot() {
+    for (departure, route) in SCHEDULE_SNAPSHOT {
+        let departure: DateTime<Utc> = departure.parse().expect("Invalid departure");
+        assert_eq!(Route::from_datetime(departure), route, "departure at {departure}");
+    }
+
+    for route in Route::ALL {
+        assert!(SCHEDULE_SNAPSHOT.iter().any(|(_, snapshot)| *snapshot == route), "{route:?} is missing from the snapshot");
+    }
+}